@@ -0,0 +1,173 @@
+//! Derive macros for parsing problem inputs.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, quote_spanned};
+use syn::{
+    parse_macro_input, spanned::Spanned, Data, DeriveInput, Expr, Fields, GenericArgument, LitStr,
+    PathArguments, Type,
+};
+
+/// Derives a fallible `FromStr` implementation from a regex with named capture groups.
+///
+/// Each field is parsed from the capture group with the same name:
+///
+/// - `Vec<T>` fields are split on whitespace and commas, or on the separator given by
+///   `#[input(split = ";")]`, and each item is parsed as a `T`.
+/// - `Option<T>` fields are `None` when their group didn't participate in the match.
+/// - Fields marked `#[input(default)]` or `#[input(default = expr)]` aren't parsed at all.
+///
+/// The pattern must match the whole line, ignoring surrounding whitespace.
+///
+/// ```ignore
+/// #[derive(FromInput)]
+/// #[pattern(r"Game (?P<id>\d+): (?P<sets>.*)")]
+/// struct Game {
+///     id: u32,
+///     #[input(split = ";")]
+///     sets: Vec<Set>,
+/// }
+/// ```
+#[proc_macro_derive(FromInput, attributes(pattern, input))]
+pub fn derive_from_input(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// How a single field is filled in.
+enum Source {
+    Capture,
+    List(Option<LitStr>),
+    Optional,
+    Default(Option<Expr>),
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let target = name.to_string();
+
+    let pattern: LitStr = (input.attrs.iter())
+        .find(|a| a.path().is_ident("pattern"))
+        .ok_or_else(|| syn::Error::new(name.span(), "missing #[pattern(r\"...\")] attribute"))?
+        .parse_args()?;
+    let anchored = LitStr::new(&format!(r"^(?:{})$", pattern.value()), pattern.span());
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(syn::Error::new(name.span(), "FromInput needs named fields")),
+        },
+        _ => {
+            return Err(syn::Error::new(
+                name.span(),
+                "FromInput can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut initializers = vec![];
+    for field in fields {
+        let ident = field
+            .ident
+            .as_ref()
+            .expect("Named fields have identifiers.");
+        let group = ident.to_string();
+
+        let source = source(field)?;
+        if !matches!(source, Source::Default(_)) && !has_group(&pattern.value(), &group) {
+            return Err(syn::Error::new(
+                pattern.span(),
+                format!("pattern has no capture group named `{}`", group),
+            ));
+        }
+
+        let value = match source {
+            Source::Capture => quote! { fields.get(#group)? },
+            Source::List(Some(separator)) => quote! { fields.list(#group, Some(#separator))? },
+            Source::List(None) => quote! { fields.list(#group, None)? },
+            Source::Optional => quote! { fields.optional(#group)? },
+            Source::Default(Some(expr)) => quote! { #expr },
+            Source::Default(None) => quote! { ::std::default::Default::default() },
+        };
+        initializers.push(quote_spanned! { field.span() => #ident: #value });
+    }
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::std::str::FromStr for #name #ty_generics #where_clause {
+            type Err = crate::utils::parse::ParseError;
+
+            fn from_str(line: &str) -> ::std::result::Result<Self, Self::Err> {
+                let line = line.trim();
+                let captures = crate::utils::parse::regex!(#anchored)
+                    .captures(line)
+                    .ok_or_else(|| crate::utils::parse::ParseError::mismatch(#target, #pattern, line))?;
+                let fields = crate::utils::parse::Fields::new(#target, line, &captures);
+
+                Ok(Self {
+                    #(#initializers,)*
+                })
+            }
+        }
+    })
+}
+
+fn source(field: &syn::Field) -> syn::Result<Source> {
+    let mut split = None;
+    let mut default = None;
+
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("input")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("split") {
+                split = Some(meta.value()?.parse::<LitStr>()?);
+                Ok(())
+            } else if meta.path.is_ident("default") {
+                default = Some(match meta.value() {
+                    Ok(value) => Some(value.parse::<Expr>()?),
+                    Err(_) => None,
+                });
+                Ok(())
+            } else {
+                Err(meta.error("expected `split = \"...\"` or `default`"))
+            }
+        })?;
+    }
+
+    if let Some(default) = default {
+        return Ok(Source::Default(default));
+    }
+
+    match wrapper(&field.ty) {
+        Some("Vec") => Ok(Source::List(split)),
+        Some("Option") if split.is_none() => Ok(Source::Optional),
+        _ if split.is_some() => Err(syn::Error::new(
+            field.ty.span(),
+            "`split` can only be used on `Vec` fields",
+        )),
+        _ => Ok(Source::Capture),
+    }
+}
+
+/// Gets the name of the generic type wrapping a field's type, such as `Vec` in `Vec<u32>`.
+fn wrapper(ty: &Type) -> Option<&'static str> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    if !matches!(args.args.first(), Some(GenericArgument::Type(_))) {
+        return None;
+    }
+
+    ["Vec", "Option"]
+        .into_iter()
+        .find(|name| segment.ident == name)
+}
+
+fn has_group(pattern: &str, group: &str) -> bool {
+    pattern.contains(&format!("(?P<{}>", group)) || pattern.contains(&format!("(?<{}>", group))
+}
@@ -29,196 +29,574 @@
 //! zoneight234      -> 14
 //! 7pqrstsixteen    -> 76
 //! ```
+//!
+//! Word digits may overlap, so `oneight` has both a one and an eight. More words can be added with
+//! the `words` parameter, such as `--param words="ten:10, un:1, deux:2"`.
+
+use std::str::FromStr;
 
-use regex::Regex;
+use aho_corasick::AhoCorasick;
 
 use crate::utils::prelude::*;
 
-pub struct Trebuchet;
+pub struct Trebuchet {
+    numerals: DigitMatcher,
+    words: DigitMatcher,
+}
+
+impl Default for Trebuchet {
+    fn default() -> Self {
+        Self {
+            numerals: DigitMatcher::new(&Vocabulary::numerals()),
+            words: DigitMatcher::new(&Vocabulary::english()),
+        }
+    }
+}
 
 impl Problem for Trebuchet {
     fn id(&self) -> &str {
         "Day 1: Trebuchet?!"
     }
 
-    fn part_1(&self, input: &str) {
-        let sum: u32 = input.split('\n').map(numeric_calibration_value).sum();
-        println!("Sum: {}", sum);
+    fn day(&self) -> u32 {
+        1
+    }
+
+    fn part_1(&self, input: &str) -> Result<Answer, SolveError> {
+        self.solve_reader(Part::One, &mut input.as_bytes())
+    }
+
+    fn part_2(&self, input: &str) -> Result<Answer, SolveError> {
+        self.solve_reader(Part::Two, &mut input.as_bytes())
+    }
+
+    fn is_streaming(&self, _part: Part) -> bool {
+        true
     }
 
-    fn part_2(&self, input: &str) {
-        let sum: u32 = input.split('\n').map(word_calibration_value).sum();
-        println!("Sum: {}", sum);
+    fn solve_lines(&self, part: Part, lines: &mut Lines) -> Result<Answer, SolveError> {
+        self.sum_calibration_values(part, lines, &mut Silent)
+    }
+
+    fn explain(
+        &self,
+        part: Part,
+        input: &str,
+        explainer: &mut dyn Explainer,
+    ) -> Option<Result<Answer, SolveError>> {
+        let mut lines = input.lines().map(|line| Ok(line.to_string()));
+        Some(self.sum_calibration_values(part, &mut lines, explainer))
+    }
+
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Some((0..size).map(|_| generate_line(rng) + "\n").collect())
+    }
+
+    fn anonymize(&self, input: &str, rng: &mut Rng) -> Option<Result<String, SolveError>> {
+        Some(Ok((input.lines())
+            .map(|line| self.anonymize_line(rng, line) + "\n")
+            .collect()))
+    }
+
+    fn configure(&mut self, params: &Parameters) -> Result<(), String> {
+        if let Some(Vocabulary(extra)) = params.parse("words")? {
+            let Vocabulary(mut words) = Vocabulary::english();
+            words.extend(extra);
+            self.words = DigitMatcher::new(&Vocabulary(words));
+        }
+        Ok(())
     }
 }
 
-/// Gets the numeric calculation value of a given line.
-fn numeric_calibration_value(line: &str) -> u32 {
-    10 * first_numeric_digit(line) + last_numeric_digit(line)
+impl Trebuchet {
+    /// Rewrites a line with other digits and words of the same lengths, and with filler letters
+    /// which can't spell a digit.
+    ///
+    /// Digits which overlap are kept as they are, since swapping them would usually pull them
+    /// apart. If the rewritten line would be read differently, another is tried, and if none of
+    /// them can be read the same way the line is kept as it is.
+    fn anonymize_line(&self, rng: &mut Rng, line: &str) -> String {
+        let groups = clusters(self.words.tokens(line));
+        let filler = |rng: &mut Rng, text: &str| -> String {
+            (text.chars())
+                .map(|c| match c.is_ascii_alphabetic() {
+                    true => char::from(*rng.choose(FILLER)),
+                    false => c,
+                })
+                .collect()
+        };
+
+        for _ in 0..ATTEMPTS {
+            let mut anonymized = String::new();
+            let mut rest = 0;
+            for &(start, end, count) in &groups {
+                anonymized += &filler(rng, &line[rest..start]);
+                let digit = &line[start..end];
+                anonymized += &match digit.parse::<u32>() {
+                    _ if count > 1 => digit.to_string(),
+                    Ok(0) => digit.to_string(),
+                    Ok(_) => rng.range(1..=9).to_string(),
+                    Err(_) if ENGLISH.contains(&digit) => {
+                        let words: Vec<&str> = (ENGLISH[1..].iter().copied())
+                            .filter(|word| word.len() == digit.len())
+                            .collect();
+                        rng.choose(&words).to_string()
+                    }
+                    Err(_) => digit.to_string(),
+                };
+                rest = end;
+            }
+            anonymized += &filler(rng, &line[rest..]);
+
+            if clusters(self.words.tokens(&anonymized)) == groups {
+                return anonymized;
+            }
+        }
+        line.to_string()
+    }
+
+    /// Sums the calibration value of each line, explaining which digits it was made from.
+    fn sum_calibration_values(
+        &self,
+        part: Part,
+        lines: &mut Lines,
+        explainer: &mut dyn Explainer,
+    ) -> Result<Answer, SolveError> {
+        let matcher = match part {
+            Part::One => &self.numerals,
+            Part::Two => &self.words,
+        };
+
+        let mut sum = Total::from(0_u32);
+        for line in lines {
+            let line = line?;
+            let value = matcher.calibration_value(&line)?;
+            explainer.explain(&mut || {
+                let digits: Vec<String> = (matcher.tokens(&line))
+                    .map(|t| format!("{} ({})", &line[t.start..t.end], t.value))
+                    .collect();
+                match digits.is_empty() {
+                    true => format!("{:?} has no digits, so its value is 0", line),
+                    false => format!(
+                        "{:?} has {}, so its value is {}",
+                        line,
+                        digits.join(", "),
+                        value
+                    ),
+                }
+            });
+            sum = num::add(&sum, &value, "summing the calibration values")?;
+        }
+        Ok(sum.into())
+    }
+}
+
+/// Letters which aren't in the name of any digit.
+const FILLER: &[u8] = b"abcdjklmpqy";
+
+/// How many times a line is rewritten before giving up on keeping its digits.
+const ATTEMPTS: usize = 100;
+
+/// Groups overlapping digits, giving the start, end, and number of digits in each group.
+fn clusters(tokens: impl Iterator<Item = Token>) -> Vec<(usize, usize, usize)> {
+    let mut tokens: Vec<Token> = tokens.collect();
+    tokens.sort_by_key(|t| (t.start, t.end));
+
+    let mut clusters: Vec<(usize, usize, usize)> = vec![];
+    for token in tokens {
+        match clusters.last_mut() {
+            Some((_, end, count)) if token.start < *end => {
+                *end = token.end.max(*end);
+                *count += 1;
+            }
+            _ => clusters.push((token.start, token.end, 1)),
+        }
+    }
+    clusters
+}
+
+/// Generates a line of letters with digits and the words for them mixed in.
+///
+/// Every line has at least one numeral so that both parts have a value for it.
+fn generate_line(rng: &mut Rng) -> String {
+    let mut pieces: Vec<String> = vec![rng.range(1..=9).to_string()];
+    for _ in 0..rng.range(0..=6) {
+        pieces.push(match rng.range(0..=2) {
+            0 => rng.range(1..=9).to_string(),
+            1 => rng.choose(&ENGLISH[1..]).to_string(),
+            _ => {
+                ((0..rng.range(1..=4)).map(|_| (b'a' + rng.range(0..=25) as u8) as char)).collect()
+            }
+        });
+    }
+    rng.shuffle(&mut pieces);
+    pieces.concat()
 }
 
-/// Extracts the first numeric digit in a given line.
-fn first_numeric_digit(line: &str) -> u32 {
-    *line
-        .chars()
-        .filter(char::is_ascii_digit)
-        .filter_map(|c| c.to_digit(10))
-        .collect::<Vec<u32>>()
-        .get(0)
-        .unwrap_or(&0)
+/// The words which stand for digits, along with their values.
+#[derive(Clone, Debug, PartialEq)]
+struct Vocabulary(Vec<(String, u32)>);
+
+const ENGLISH: [&str; 10] = [
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+
+impl Vocabulary {
+    pub fn numerals() -> Self {
+        Self((0..10).map(|d| (d.to_string(), d)).collect())
+    }
+
+    /// Gets the numerals along with the English words for them.
+    pub fn english() -> Self {
+        let Vocabulary(mut words) = Self::numerals();
+        words.extend((ENGLISH.iter()).zip(0..).map(|(w, d)| (w.to_string(), d)));
+        Self(words)
+    }
 }
 
-/// Extracts the last numeric digit in a given line.
-fn last_numeric_digit(line: &str) -> u32 {
-    *line
-        .chars()
-        .filter(char::is_ascii_digit)
-        .filter_map(|c| c.to_digit(10))
-        .rev()
-        .collect::<Vec<u32>>()
-        .get(0)
-        .unwrap_or(&0)
+impl FromStr for Vocabulary {
+    type Err = String;
+
+    /// Parses a list of words and their values, such as `ten:10, un:1`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        (value.split(','))
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| match entry.split_once(':') {
+                Some((word, digit)) if !word.trim().is_empty() => (digit.trim().parse())
+                    .map(|digit| (word.trim().to_string(), digit))
+                    .map_err(|err| format!("{:?} has an invalid value: {}", entry, err)),
+                _ => Err(format!("{:?} should look like `word:value`", entry)),
+            })
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
 }
 
-/// Gets the word calculation value of a given line.
-fn word_calibration_value(line: &str) -> u32 {
-    10 * first_word_digit(line) + last_word_digit(line)
+/// A single occurrence of a digit in a line.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Token {
+    start: usize,
+    end: usize,
+    value: u32,
 }
 
-const MATCH_FIRST_DIGIT: &'static str = r"(\d|zero|one|two|three|four|five|six|seven|eight|nine)";
-
-/// Extracts the first word digit in a given line.
-fn first_word_digit(line: &str) -> u32 {
-    (Regex::new(MATCH_FIRST_DIGIT).ok())
-        .and_then(|re| re.captures(line))
-        .and_then(|cap| cap.get(1))
-        .and_then(|ch| match ch.as_str() {
-            "0" | "zero" => Some(0),
-            "1" | "one" => Some(1),
-            "2" | "two" => Some(2),
-            "3" | "three" => Some(3),
-            "4" | "four" => Some(4),
-            "5" | "five" => Some(5),
-            "6" | "six" => Some(6),
-            "7" | "seven" => Some(7),
-            "8" | "eight" => Some(8),
-            "9" | "nine" => Some(9),
-            _ => None,
-        })
-        .unwrap_or(0)
+/// Finds every digit in a line in a single pass, including ones which overlap.
+struct DigitMatcher {
+    automaton: AhoCorasick,
+    values: Vec<u32>,
 }
 
-const MATCH_LAST_DIGIT: &'static str = r"(\d|orez|eno|owt|eerht|ruof|evif|xis|neves|thgie|enin)";
-
-/// Extracts the last word digit in a given line.
-fn last_word_digit(line: &str) -> u32 {
-    let line = line.chars().rev().collect::<String>();
-    (Regex::new(MATCH_LAST_DIGIT).ok())
-        .and_then(|re| re.captures(line.as_str()))
-        .and_then(|cap| cap.get(1))
-        .and_then(|ch| match ch.as_str() {
-            "0" | "orez" => Some(0),
-            "1" | "eno" => Some(1),
-            "2" | "owt" => Some(2),
-            "3" | "eerht" => Some(3),
-            "4" | "ruof" => Some(4),
-            "5" | "evif" => Some(5),
-            "6" | "xis" => Some(6),
-            "7" | "neves" => Some(7),
-            "8" | "thgie" => Some(8),
-            "9" | "enin" => Some(9),
-            _ => None,
+impl DigitMatcher {
+    pub fn new(vocabulary: &Vocabulary) -> Self {
+        let Vocabulary(words) = vocabulary;
+        Self {
+            automaton: AhoCorasick::new(words.iter().map(|(word, _)| word))
+                .expect("Vocabulary was too large to match."),
+            values: words.iter().map(|&(_, value)| value).collect(),
+        }
+    }
+
+    /// Iterates over every digit in the line, in order of where they end.
+    pub fn tokens<'a>(&'a self, line: &'a str) -> impl Iterator<Item = Token> + 'a {
+        (self.automaton.find_overlapping_iter(line)).map(|m| Token {
+            start: m.start(),
+            end: m.end(),
+            value: self.values[m.pattern().as_usize()],
         })
-        .unwrap_or(0)
+    }
+
+    /// Finds the first and last digits in the line.
+    ///
+    /// When several digits start or end at the same place, the longest one wins, so `seventeen`
+    /// is read as 17 rather than 7 if both are in the vocabulary.
+    pub fn first_and_last(&self, line: &str) -> Option<(u32, u32)> {
+        let mut tokens = self.tokens(line);
+        let token = tokens.next()?;
+        let (first, last) = tokens.fold((token, token), |(first, last), token| {
+            let longer = |a: Token, b: Token| a.end - a.start > b.end - b.start;
+            let first = match token.start.cmp(&first.start) {
+                std::cmp::Ordering::Less => token,
+                std::cmp::Ordering::Equal if longer(token, first) => token,
+                _ => first,
+            };
+            let last = match token.end.cmp(&last.end) {
+                std::cmp::Ordering::Greater => token,
+                std::cmp::Ordering::Equal if longer(token, last) => token,
+                _ => last,
+            };
+            (first, last)
+        });
+        Some((first.value, last.value))
+    }
+
+    /// Gets the calibration value of the line, which is zero if it has no digits.
+    ///
+    /// Words can be given any value, so the value is checked in case it doesn't fit.
+    pub fn calibration_value(&self, line: &str) -> Result<Total, SolveError> {
+        let Some((first, last)) = self.first_and_last(line) else {
+            return Ok(Total::from(0_u32));
+        };
+        let tens = num::mul(
+            &Total::from(10_u32),
+            &Total::from(first),
+            "reading a calibration value",
+        )?;
+        num::add(&tens, &Total::from(last), "reading a calibration value")
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use proptest::{prop_assert_eq, proptest};
+
+    use crate::utils::{explain::Trace, properties};
+
     use super::*;
 
+    fn numerals() -> DigitMatcher {
+        DigitMatcher::new(&Vocabulary::numerals())
+    }
+
+    fn words() -> DigitMatcher {
+        DigitMatcher::new(&Vocabulary::english())
+    }
+
+    fn first(matcher: &DigitMatcher, line: &str) -> Option<u32> {
+        matcher.first_and_last(line).map(|(first, _)| first)
+    }
+
+    fn last(matcher: &DigitMatcher, line: &str) -> Option<u32> {
+        matcher.first_and_last(line).map(|(_, last)| last)
+    }
+
+    /// Finds the first and last digits by trying every word at every place in the line.
+    fn scan(vocabulary: &Vocabulary, line: &str) -> Option<(u32, u32)> {
+        let Vocabulary(words) = vocabulary;
+        let tokens: Vec<(usize, usize, u32)> = (0..line.len())
+            .flat_map(|i| {
+                (words.iter())
+                    .filter(move |(word, _)| line[i..].starts_with(word.as_str()))
+                    .map(move |(word, value)| (i, i + word.len(), *value))
+            })
+            .collect();
+        let first = tokens
+            .iter()
+            .min_by_key(|&&(start, end, _)| (start, usize::MAX - end))?;
+        let last = tokens
+            .iter()
+            .max_by_key(|&&(start, end, _)| (end, end - start))?;
+        Some((first.2, last.2))
+    }
+
     #[test]
     fn gets_numeric_calibration_values() {
-        assert_eq!(numeric_calibration_value("1abc2"), 12);
-        assert_eq!(numeric_calibration_value("pqr3stu8vwx"), 38);
-        assert_eq!(numeric_calibration_value("a1b2c3d4e5f"), 15);
-        assert_eq!(numeric_calibration_value("treb7uchet"), 77);
+        let numerals = numerals();
+        assert_eq!(numerals.calibration_value("1abc2"), Ok(Total::from(12_u32)));
+        assert_eq!(
+            numerals.calibration_value("pqr3stu8vwx"),
+            Ok(Total::from(38_u32))
+        );
+        assert_eq!(
+            numerals.calibration_value("a1b2c3d4e5f"),
+            Ok(Total::from(15_u32))
+        );
+        assert_eq!(
+            numerals.calibration_value("treb7uchet"),
+            Ok(Total::from(77_u32))
+        );
+        assert_eq!(numerals.calibration_value("one"), Ok(Total::from(0_u32)));
     }
 
     #[test]
     fn gets_first_numeric_digits() {
-        assert_eq!(first_numeric_digit(""), 0);
-        assert_eq!(first_numeric_digit("1"), 1);
-        assert_eq!(first_numeric_digit("123"), 1);
-        assert_eq!(first_numeric_digit("abc123xyz"), 1);
+        let numerals = numerals();
+        assert_eq!(first(&numerals, ""), None);
+        assert_eq!(first(&numerals, "1"), Some(1));
+        assert_eq!(first(&numerals, "123"), Some(1));
+        assert_eq!(first(&numerals, "abc123xyz"), Some(1));
     }
 
     #[test]
     fn gets_last_numeric_digits() {
-        assert_eq!(last_numeric_digit(""), 0);
-        assert_eq!(last_numeric_digit("1"), 1);
-        assert_eq!(last_numeric_digit("123"), 3);
-        assert_eq!(last_numeric_digit("abc123xyz"), 3);
+        let numerals = numerals();
+        assert_eq!(last(&numerals, ""), None);
+        assert_eq!(last(&numerals, "1"), Some(1));
+        assert_eq!(last(&numerals, "123"), Some(3));
+        assert_eq!(last(&numerals, "abc123xyz"), Some(3));
     }
 
     #[test]
     fn gets_word_calibration_values() {
-        assert_eq!(word_calibration_value("two1nine"), 29);
-        assert_eq!(word_calibration_value("eightwothree"), 83);
-        assert_eq!(word_calibration_value("abcone2threexyz"), 13);
-        assert_eq!(word_calibration_value("xtwone3four"), 24);
-        assert_eq!(word_calibration_value("4nineeightseven2"), 42);
-        assert_eq!(word_calibration_value("zoneight234"), 14);
-        assert_eq!(word_calibration_value("7pqrstsixteen"), 76);
-        assert_eq!(word_calibration_value("oneight"), 18);
+        let words = words();
+        assert_eq!(words.calibration_value("two1nine"), Ok(Total::from(29_u32)));
+        assert_eq!(
+            words.calibration_value("eightwothree"),
+            Ok(Total::from(83_u32))
+        );
+        assert_eq!(
+            words.calibration_value("abcone2threexyz"),
+            Ok(Total::from(13_u32))
+        );
+        assert_eq!(
+            words.calibration_value("xtwone3four"),
+            Ok(Total::from(24_u32))
+        );
+        assert_eq!(
+            words.calibration_value("4nineeightseven2"),
+            Ok(Total::from(42_u32))
+        );
+        assert_eq!(
+            words.calibration_value("zoneight234"),
+            Ok(Total::from(14_u32))
+        );
+        assert_eq!(
+            words.calibration_value("7pqrstsixteen"),
+            Ok(Total::from(76_u32))
+        );
+        assert_eq!(words.calibration_value("oneight"), Ok(Total::from(18_u32)));
+    }
+
+    #[test]
+    fn gets_first_and_last_word_digits() {
+        let words = words();
+        assert_eq!(first(&words, ""), None);
+        assert_eq!(last(&words, ""), None);
+        for (digit, word) in (0..).zip(ENGLISH) {
+            for line in [digit.to_string().as_str(), word] {
+                assert_eq!(first(&words, line), Some(digit));
+                assert_eq!(last(&words, line), Some(digit));
+            }
+        }
+    }
+
+    #[test]
+    fn finds_overlapping_tokens() {
+        let values: Vec<u32> = words().tokens("oneightwo").map(|t| t.value).collect();
+        assert_eq!(values, vec![1, 8, 2]);
     }
 
     #[test]
-    fn gets_first_word_digits() {
-        assert_eq!(first_word_digit(""), 0);
-        assert_eq!(first_word_digit("0"), 0);
-        assert_eq!(first_word_digit("zero"), 0);
-        assert_eq!(first_word_digit("1"), 1);
-        assert_eq!(first_word_digit("one"), 1);
-        assert_eq!(first_word_digit("2"), 2);
-        assert_eq!(first_word_digit("two"), 2);
-        assert_eq!(first_word_digit("3"), 3);
-        assert_eq!(first_word_digit("three"), 3);
-        assert_eq!(first_word_digit("4"), 4);
-        assert_eq!(first_word_digit("four"), 4);
-        assert_eq!(first_word_digit("5"), 5);
-        assert_eq!(first_word_digit("five"), 5);
-        assert_eq!(first_word_digit("6"), 6);
-        assert_eq!(first_word_digit("six"), 6);
-        assert_eq!(first_word_digit("7"), 7);
-        assert_eq!(first_word_digit("seven"), 7);
-        assert_eq!(first_word_digit("8"), 8);
-        assert_eq!(first_word_digit("eight"), 8);
-        assert_eq!(first_word_digit("9"), 9);
-        assert_eq!(first_word_digit("nine"), 9);
+    fn prefers_the_longest_words() {
+        let vocabulary: Vocabulary = "seventeen:17, teen:10".parse().unwrap();
+        let Vocabulary(mut words) = Vocabulary::english();
+        words.extend(vocabulary.0);
+        let words = DigitMatcher::new(&Vocabulary(words));
+        assert_eq!(words.first_and_last("seventeen"), Some((17, 17)));
+        assert_eq!(words.first_and_last("xseventeenx"), Some((17, 17)));
     }
 
     #[test]
-    fn gets_last_word_digits() {
-        assert_eq!(last_word_digit(""), 0);
-        assert_eq!(last_word_digit("0"), 0);
-        assert_eq!(last_word_digit("zero"), 0);
-        assert_eq!(last_word_digit("1"), 1);
-        assert_eq!(last_word_digit("one"), 1);
-        assert_eq!(last_word_digit("2"), 2);
-        assert_eq!(last_word_digit("two"), 2);
-        assert_eq!(last_word_digit("3"), 3);
-        assert_eq!(last_word_digit("three"), 3);
-        assert_eq!(last_word_digit("4"), 4);
-        assert_eq!(last_word_digit("four"), 4);
-        assert_eq!(last_word_digit("5"), 5);
-        assert_eq!(last_word_digit("five"), 5);
-        assert_eq!(last_word_digit("6"), 6);
-        assert_eq!(last_word_digit("six"), 6);
-        assert_eq!(last_word_digit("7"), 7);
-        assert_eq!(last_word_digit("seven"), 7);
-        assert_eq!(last_word_digit("8"), 8);
-        assert_eq!(last_word_digit("eight"), 8);
-        assert_eq!(last_word_digit("9"), 9);
-        assert_eq!(last_word_digit("nine"), 9);
+    fn parses_vocabularies() {
+        assert_eq!(
+            "un:1, deux: 2".parse(),
+            Ok(Vocabulary(vec![
+                ("un".to_string(), 1),
+                ("deux".to_string(), 2)
+            ]))
+        );
+        assert!("un".parse::<Vocabulary>().is_err());
+        assert!("un:one".parse::<Vocabulary>().is_err());
+    }
+
+    #[test]
+    fn explains_each_line() {
+        let mut trace = Trace::new(vec![]);
+        let answer = Trebuchet::default().explain(Part::Two, "xtwone3\nabc\n", &mut trace);
+        assert_eq!(answer.unwrap().unwrap(), 23_u32.into());
+
+        let text = String::from_utf8(trace.finish().unwrap()).unwrap();
+        assert_eq!(
+            text,
+            "  \"xtwone3\" has two (2), one (1), 3 (3), so its value is 23\n  \
+             \"abc\" has no digits, so its value is 0\n"
+        );
+    }
+
+    #[test]
+    fn generates_lines_with_numerals() {
+        let problem = Trebuchet::default();
+        let input = problem.generate(&mut Rng::new(0), 50).unwrap();
+        assert_eq!(input.lines().count(), 50);
+        assert!(input
+            .lines()
+            .all(|line| (problem.numerals.calibration_value(line))
+                .is_ok_and(|value| value > Total::from(0_u32))));
+        assert_eq!(problem.generate(&mut Rng::new(0), 50), Some(input));
+    }
+
+    #[test]
+    fn anonymizes_lines_without_moving_digits() {
+        let problem = Trebuchet::default();
+        let input = "two1nine\nxtwone3four\n4nineeightseven2\nzoneight234\n";
+        let anonymized = problem.anonymize(input, &mut Rng::new(0)).unwrap().unwrap();
+        assert_ne!(anonymized, input);
+
+        for (line, original) in anonymized.lines().zip(input.lines()) {
+            let tokens: Vec<(usize, usize)> = (problem.words.tokens(line))
+                .map(|t| (t.start, t.end))
+                .collect();
+            let originals: Vec<(usize, usize)> = (problem.words.tokens(original))
+                .map(|t| (t.start, t.end))
+                .collect();
+            assert_eq!(tokens, originals);
+        }
+        assert!(anonymized.lines().nth(1).unwrap().contains("twone"));
+    }
+
+    #[test]
+    fn keeps_lines_which_can_never_be_read_the_same_way() {
+        // Filler letters spell the extra word, so no rewrite of this line avoids adding digits.
+        let mut problem = Trebuchet::default();
+        let params = [("words".to_string(), "a:7".to_string())];
+        problem.configure(&params.into_iter().collect()).unwrap();
+
+        let input = format!("one{}two\n", "x".repeat(200));
+        let anonymized = problem
+            .anonymize(&input, &mut Rng::new(0))
+            .unwrap()
+            .unwrap();
+        assert_eq!(anonymized, input);
+        assert_eq!(problem.part_2(&anonymized), problem.part_2(&input));
+    }
+
+    #[test]
+    fn solves_with_extra_words() {
+        let mut problem = Trebuchet::default();
+        let params = [("words".to_string(), "un:1, deux:2".to_string())];
+        problem.configure(&params.into_iter().collect()).unwrap();
+        assert_eq!(problem.part_2("deuxone\nunxtwo\n").unwrap(), 33_u32.into());
+        assert_eq!(problem.part_1("deuxone\nunxtwo\n").unwrap(), 0_u32.into());
+
+        // Ten times the largest digit doesn't fit in the digits' own type.
+        let params = [("words".to_string(), "big:4000000000".to_string())];
+        problem.configure(&params.into_iter().collect()).unwrap();
+        assert_eq!(problem.part_2("big1\n").unwrap(), 40_000_000_001_u64.into());
+    }
+
+    proptest! {
+        #[test]
+        fn finds_the_same_digits_as_scanning_every_word(line in "[efghinorstuvwxz0-9]{0,24}") {
+            for vocabulary in [Vocabulary::numerals(), Vocabulary::english()] {
+                let matcher = DigitMatcher::new(&vocabulary);
+                prop_assert_eq!(matcher.first_and_last(&line), scan(&vocabulary, &line));
+            }
+        }
+
+        #[test]
+        fn anonymizes_generated_lines_without_moving_digits(
+            mut rng in properties::rngs(),
+            size in 1..20_usize,
+        ) {
+            let problem = Trebuchet::default();
+            let input = problem.generate(&mut rng, size).unwrap();
+            let anonymized = problem.anonymize(&input, &mut rng).unwrap().unwrap();
+            for (line, original) in anonymized.lines().zip(input.lines()) {
+                prop_assert_eq!(
+                    clusters(problem.words.tokens(line)),
+                    clusters(problem.words.tokens(original))
+                );
+            }
+        }
     }
 }
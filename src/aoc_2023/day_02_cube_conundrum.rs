@@ -17,149 +17,561 @@
 //! IDs of the games which are possible with the bag we have.
 //!
 //! ## Part 2
+//!
+//! Find the fewest cubes of each color that would make each game possible. The "power" of a set
+//! of cubes is the product of the number of each color in it. Find the sum of the powers of the
+//! fewest cubes for each game. For the previous example, the sum is 2286.
+//!
+//! The bag can be changed with the `bag` parameter, such as `--param bag="3 red, 4 purple"`.
+
+use std::{collections::BTreeMap, fmt::Display, str::FromStr};
+
+use crate::utils::{anonymize, prelude::*};
 
-use regex::Regex;
+pub struct CubeConundrum {
+    bag: Set,
+}
+
+impl Default for CubeConundrum {
+    fn default() -> Self {
+        Self {
+            bag: DEFAULT_BAG
+                .parse()
+                .expect("Default bag was not a valid set."),
+        }
+    }
+}
 
-use crate::utils::prelude::*;
+const DEFAULT_BAG: &str = "12 red, 13 green, 14 blue";
 
-pub struct CubeConundrum;
+/// The colors which count towards a set's power even when it has none of them.
+const PRIMARY_COLORS: [&str; 3] = ["red", "green", "blue"];
 
 impl Problem for CubeConundrum {
     fn id(&self) -> &str {
         "Day 2: Cube Conundrum"
     }
 
-    fn part_1(&self, input: &str) {
-        let bag = Set::new(12, 13, 14);
-        let sum: u32 = load_games(input)
-            .iter()
-            .filter(|g| g.sets.iter().all(|s| s.fits_within(&bag)))
-            .map(|g| g.id)
-            .sum();
-        println!("Sum: {}", sum);
+    fn day(&self) -> u32 {
+        2
+    }
+
+    fn part_1(&self, input: &str) -> Result<Answer, SolveError> {
+        self.solve_reader(Part::One, &mut input.as_bytes())
+    }
+
+    fn part_2(&self, input: &str) -> Result<Answer, SolveError> {
+        self.solve_reader(Part::Two, &mut input.as_bytes())
     }
 
-    fn part_2(&self, input: &str) {
-        let sum: u32 = load_games(input)
-            .iter()
-            .map(Game::min_set)
-            .map(Set::power)
-            .sum();
-        println!("Sum: {}", sum);
+    fn is_streaming(&self, _part: Part) -> bool {
+        true
+    }
+
+    fn solve_lines(&self, part: Part, lines: &mut Lines) -> Result<Answer, SolveError> {
+        self.sum_games(part, lines, &mut Silent)
+    }
+
+    fn explain(
+        &self,
+        part: Part,
+        input: &str,
+        explainer: &mut dyn Explainer,
+    ) -> Option<Result<Answer, SolveError>> {
+        let mut lines = input.lines().map(|line| Ok(line.to_string()));
+        Some(self.sum_games(part, &mut lines, explainer))
+    }
+
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Some(
+            (1..=size)
+                .map(|id| self.generate_game(rng, id) + "\n")
+                .collect(),
+        )
+    }
+
+    fn anonymize(&self, input: &str, rng: &mut Rng) -> Option<Result<String, SolveError>> {
+        Some(Ok((input.lines())
+            .map(|line| match line.parse::<Game>() {
+                Ok(game) => self.anonymize_game(rng, &game) + "\n",
+                Err(_) => anonymize::renumber(rng, line) + "\n",
+            })
+            .collect()))
+    }
+
+    fn configure(&mut self, params: &Parameters) -> Result<(), String> {
+        if let Some(bag) = params.parse("bag")? {
+            self.bag = bag;
+        }
+        Ok(())
+    }
+}
+
+impl CubeConundrum {
+    /// Sums the IDs of the possible games or the powers of every game, explaining each one.
+    fn sum_games(
+        &self,
+        part: Part,
+        lines: &mut Lines,
+        explainer: &mut dyn Explainer,
+    ) -> Result<Answer, SolveError> {
+        let mut sum = Total::from(0_u32);
+        for game in parse::stream::<Game>(lines) {
+            let game = game?;
+            let (value, computing) = match part {
+                Part::One => match game.impossible_draw(&self.bag) {
+                    Some((i, draw)) => {
+                        explainer.explain(&mut || {
+                            format!(
+                                "Game {} is impossible, since draw {} ({}) doesn't fit in the bag",
+                                game.id,
+                                i + 1,
+                                draw
+                            )
+                        });
+                        continue;
+                    }
+                    None => {
+                        explainer.explain(&mut || match game.total_drawn() {
+                            Ok(total) => {
+                                format!("Game {} is possible, drawing {} in all", game.id, total)
+                            }
+                            Err(_) => format!("Game {} is possible", game.id),
+                        });
+                        (Total::from(game.id), "summing the game IDs")
+                    }
+                },
+                Part::Two => {
+                    let bag = game.minimal_bag();
+                    let power = bag.power()?;
+                    explainer.explain(&mut || {
+                        format!(
+                            "Game {} needs at least {}, which has a power of {}",
+                            game.id, bag, power
+                        )
+                    });
+                    (power, "summing the powers of the sets")
+                }
+            };
+            sum = num::add(&sum, &value, computing)?;
+        }
+        Ok(sum.into())
     }
 }
 
-fn load_games(input: &str) -> Vec<Game> {
-    input
-        .split('\n')
-        .filter(|l| 0 < l.len())
-        .map(Game::from)
-        .collect()
+impl CubeConundrum {
+    /// Rewrites a game with new counts and the colors of each draw in a new order.
+    ///
+    /// Counts which fit in the bag are replaced with others which do, and the rest with others
+    /// which don't, so the same draws are impossible.
+    fn anonymize_game(&self, rng: &mut Rng, game: &Game) -> String {
+        let draws: Vec<String> = (game.sets.iter())
+            .map(|set| {
+                let mut cubes: Vec<String> = (set.0.iter())
+                    .map(|(color, &count)| {
+                        let most = u64::from(self.bag.count(color));
+                        let count = u64::from(count);
+                        let count = match count <= most {
+                            true => rng.range(1..=most),
+                            false => rng.range(most + 1..=count),
+                        };
+                        format!("{} {}", count, color)
+                    })
+                    .collect();
+                rng.shuffle(&mut cubes);
+                cubes.join(", ")
+            })
+            .collect();
+        format!("Game {}: {}", game.id, draws.join("; "))
+            .trim_end()
+            .to_string()
+    }
+
+    /// Generates a game drawing the colors in the bag, sometimes drawing more than it holds.
+    fn generate_game(&self, rng: &mut Rng, id: usize) -> String {
+        let colors: Vec<(&String, u32)> = (self.bag.0.iter())
+            .map(|(color, &count)| (color, count))
+            .collect();
+        if colors.is_empty() {
+            return format!("Game {}:", id);
+        }
+
+        let draws: Vec<String> = (0..rng.range(1..=6))
+            .map(|_| {
+                let mut draw = colors.clone();
+                rng.shuffle(&mut draw);
+                draw.truncate(rng.range(1..=draw.len() as u64) as usize);
+                (draw.into_iter())
+                    .map(|(color, most)| {
+                        let count = rng.range(1..=u64::from(most) * 3 / 2 + 1);
+                        format!("{} {}", count, color)
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+            .collect();
+        format!("Game {}: {}", id, draws.join("; "))
+    }
 }
 
+#[derive(Debug, PartialEq, FromInput)]
+#[pattern(r"Game (?P<id>\d+):(?P<sets>.*)")]
 struct Game {
     id: u32,
+    #[input(split = ";")]
     sets: Vec<Set>,
 }
 
 impl Game {
-    pub fn min_set(&self) -> Set {
-        let r = self.sets.iter().map(|s| s.red).max().unwrap();
-        let g = self.sets.iter().map(|s| s.green).max().unwrap();
-        let b = self.sets.iter().map(|s| s.blue).max().unwrap();
-        Set::new(r, g, b)
+    /// Gets the fewest cubes of each color which would make this game possible.
+    pub fn minimal_bag(&self) -> Set {
+        let mut bag = Set::default();
+        for (color, &count) in self.sets.iter().flat_map(|s| s.0.iter()) {
+            let most = bag.0.entry(color.clone()).or_default();
+            *most = count.max(*most);
+        }
+        bag
     }
-}
-
-const MATCH_GAME_ID: &'static str = r"Game (\d+)";
 
-impl From<&str> for Game {
-    fn from(value: &str) -> Self {
-        Self {
-            id: match_u32(MATCH_GAME_ID, value),
-            sets: value.split(';').map(Set::from).collect(),
+    /// Counts how many cubes of each color were drawn over the whole game.
+    pub fn total_drawn(&self) -> Result<Set, SolveError> {
+        let mut total = Set::default();
+        for (color, &count) in self.sets.iter().flat_map(|s| s.0.iter()) {
+            let sum = total.0.entry(color.clone()).or_default();
+            *sum =
+                (sum.checked_add(count)).ok_or(SolveError::Overflow("counting the cubes drawn"))?;
         }
+        Ok(total)
+    }
+
+    /// Finds the first draw which needed more cubes than the bag has, along with its index.
+    pub fn impossible_draw(&self, bag: &Set) -> Option<(usize, &Set)> {
+        (self.sets.iter().enumerate()).find(|(_, s)| !s.fits_within(bag))
     }
 }
 
-#[derive(Debug, PartialEq)]
-struct Set {
-    red: u32,
-    green: u32,
-    blue: u32,
+impl Display for Game {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sets: Vec<String> = self.sets.iter().map(Set::to_string).collect();
+        write!(f, "Game {}: {}", self.id, sets.join("; "))
+    }
 }
 
+/// How many cubes of each color there are in a draw or a bag.
+///
+/// Colors with no cubes are left out, so sets with the same cubes are always equal.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Set(BTreeMap<String, u32>);
+
 impl Set {
-    fn new(red: u32, green: u32, blue: u32) -> Self {
-        Self { red, green, blue }
+    pub fn count(&self, color: &str) -> u32 {
+        self.0.get(color).copied().unwrap_or(0)
     }
 
-    fn fits_within(&self, other: &Set) -> bool {
-        self.red <= other.red && self.green <= other.green && self.blue <= other.blue
+    pub fn fits_within(&self, other: &Set) -> bool {
+        (self.0.iter()).all(|(color, &count)| count <= other.count(color))
     }
 
-    fn power(self) -> u32 {
-        self.red * self.green * self.blue
+    /// Multiplies the counts of every color in this set.
+    ///
+    /// Red, green, and blue count as zero when the set has none of them, so a game which never
+    /// drew one of them has no power.
+    pub fn power(&self) -> Result<Total, SolveError> {
+        let colors = (PRIMARY_COLORS.iter())
+            .filter(|color| !self.0.contains_key(**color))
+            .map(|_| 0);
+        (self.0.values().copied().chain(colors))
+            .map(Total::from)
+            .checked_product("multiplying the cubes in a set")
     }
 }
 
-const MATCH_RED: &'static str = r"(\d+) red";
-const MATCH_GREEN: &'static str = r"(\d+) green";
-const MATCH_BLUE: &'static str = r"(\d+) blue";
+impl FromStr for Set {
+    type Err = ParseError;
 
-impl From<&str> for Set {
-    fn from(value: &str) -> Self {
-        Self {
-            red: match_u32(MATCH_RED, value),
-            green: match_u32(MATCH_GREEN, value),
-            blue: match_u32(MATCH_BLUE, value),
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut set = Set::default();
+        for cubes in value.split(',').filter(|c| !c.trim().is_empty()) {
+            let Cubes { count, color } = cubes.parse()?;
+            if count == 0 {
+                continue;
+            }
+
+            let total = set.0.entry(color).or_default();
+            *total = total
+                .checked_add(count)
+                .ok_or_else(|| ParseError::InvalidField {
+                    target: "Set",
+                    field: "count",
+                    value: count.to_string(),
+                    reason: "too many cubes in total".to_string(),
+                    line: value.trim().to_string(),
+                })?;
         }
+        Ok(set)
     }
 }
 
-fn match_u32(pat: &str, value: &str) -> u32 {
-    (Regex::new(pat).ok())
-        .and_then(|re| re.captures(value))
-        .and_then(|cap| cap.get(1))
-        .and_then(|x| x.as_str().parse().ok())
-        .unwrap_or(0)
+impl Display for Set {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cubes: Vec<String> = (self.0.iter())
+            .map(|(color, count)| format!("{} {}", count, color))
+            .collect();
+        match cubes.is_empty() {
+            true => write!(f, "no cubes"),
+            false => write!(f, "{}", cubes.join(", ")),
+        }
+    }
+}
+
+/// A number of cubes of a single color.
+#[derive(FromInput)]
+#[pattern(r"(?P<count>\d+) (?P<color>[a-z]+)")]
+struct Cubes {
+    count: u32,
+    color: String,
 }
 
 #[cfg(test)]
 mod tests {
+    use proptest::{
+        prelude::{prop, Strategy},
+        prop_assert, prop_assert_eq, proptest,
+    };
+
+    use crate::utils::{explain::Trace, properties};
+
     use super::*;
 
+    const EXAMPLE: &str = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
+";
+
+    fn set(text: &str) -> Set {
+        text.parse().unwrap()
+    }
+
+    fn game(line: &str) -> Game {
+        line.parse().unwrap()
+    }
+
     #[test]
     fn creates_sets_from_strings() {
-        assert_eq!(Set::from(""), Set::new(0, 0, 0));
-        assert_eq!(Set::from("1 red"), Set::new(1, 0, 0));
-        assert_eq!(Set::from("1 green"), Set::new(0, 1, 0));
-        assert_eq!(Set::from("1 blue"), Set::new(0, 0, 1));
-        assert_eq!(Set::from("1 red, 2 green, 3 blue"), Set::new(1, 2, 3));
+        assert_eq!("".parse(), Ok(Set::default()));
+        assert_eq!(set("1 red").count("red"), 1);
+        assert_eq!(set("1 green").count("green"), 1);
+        assert_eq!(set("1 blue").count("blue"), 1);
+        assert_eq!(set("1 purple").count("purple"), 1);
+
+        let cubes = set("1 red, 2 green, 3 blue, 4 red");
+        assert_eq!(
+            [
+                cubes.count("red"),
+                cubes.count("green"),
+                cubes.count("blue")
+            ],
+            [5, 2, 3]
+        );
+        assert_eq!(set("0 red, 1 blue"), set("1 blue"));
+    }
+
+    #[test]
+    fn rejects_malformed_sets() {
+        assert!("red 1".parse::<Set>().is_err());
+        assert!("1 red blue".parse::<Set>().is_err());
     }
 
     #[test]
     fn creates_games_from_strings() {
-        let game = Game::from("Game 123: 1 red; 2 green; 3 blue");
+        let game = game("Game 123: 1 red; 2 green; 3 blue");
         assert_eq!(game.id, 123);
-        assert!(game.sets.contains(&Set::new(1, 0, 0)));
-        assert!(game.sets.contains(&Set::new(0, 2, 0)));
-        assert!(game.sets.contains(&Set::new(0, 0, 3)));
+        assert!(game.sets.contains(&set("1 red")));
+        assert!(game.sets.contains(&set("2 green")));
+        assert!(game.sets.contains(&set("3 blue")));
     }
 
     #[test]
     fn loads_games_from_file() {
         let file = "Game 123:\nGame 456:\nGame 789:\n";
-        let games = load_games(file);
+        let games: Vec<Game> = parse::lines(file).unwrap();
         assert_eq!(123, games[0].id);
         assert_eq!(456, games[1].id);
         assert_eq!(789, games[2].id);
     }
 
+    #[test]
+    fn rejects_malformed_games() {
+        assert!("Game x: 1 red".parse::<Game>().is_err());
+        assert!("Game 1: 1 red; purple".parse::<Game>().is_err());
+    }
+
+    #[test]
+    fn rejects_sets_with_too_many_cubes() {
+        assert!("4294967295 red, 1 red".parse::<Set>().is_err());
+    }
+
+    #[test]
+    fn gets_powers_without_overflowing() {
+        assert_eq!(
+            set("2 red, 3 green, 4 blue").power(),
+            Ok(Total::from(24_u32))
+        );
+        assert_eq!(set("2 red, 3 green").power(), Ok(Total::from(0_u32)));
+        assert_eq!(
+            set("2 red, 3 green, 4 blue, 5 purple").power(),
+            Ok(Total::from(120_u32))
+        );
+
+        let max = u32::MAX;
+        let huge = set(&format!("{0} red, {0} green, {0} blue", max)).power();
+        assert_eq!(huge.is_err(), !cfg!(feature = "bigint"));
+    }
+
     #[test]
     fn fits_sets_within_other_sets() {
-        assert!(Set::new(0, 0, 0).fits_within(&Set::new(0, 0, 0)));
-        assert!(Set::new(0, 0, 0).fits_within(&Set::new(1, 1, 1)));
-        assert!(!Set::new(1, 1, 1).fits_within(&Set::new(0, 0, 0)));
+        assert!(set("").fits_within(&set("")));
+        assert!(set("").fits_within(&set("1 red, 1 green, 1 blue")));
+        assert!(!set("1 red, 1 green, 1 blue").fits_within(&set("")));
+        assert!(set("1 red").fits_within(&set("1 red, 1 blue")));
+        assert!(!set("1 purple").fits_within(&set(DEFAULT_BAG)));
+    }
+
+    #[test]
+    fn finds_minimal_bags() {
+        let game = game("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green");
+        assert_eq!(game.minimal_bag(), set("4 red, 2 green, 6 blue"));
+        assert!(game.sets.iter().all(|s| s.fits_within(&game.minimal_bag())));
+    }
+
+    #[test]
+    fn counts_total_cubes_drawn() {
+        let game = game("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green");
+        assert_eq!(game.total_drawn(), Ok(set("5 red, 4 green, 9 blue")));
+    }
+
+    #[test]
+    fn finds_impossible_draws() {
+        let bag = set(DEFAULT_BAG);
+        let game = game("Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red");
+        assert_eq!(game.impossible_draw(&bag), Some((0, &game.sets[0])));
+        assert_eq!(game.impossible_draw(&set("20 red, 13 green, 6 blue")), None);
+    }
+
+    #[test]
+    fn explains_which_draw_was_impossible() {
+        let mut trace = Trace::new(vec![]);
+        let problem = CubeConundrum::default();
+        let input = "Game 1: 1 red; 2 red, 1 blue\nGame 2: 2 blue; 3 green, 20 red\n";
+        let answer = problem.explain(Part::One, input, &mut trace);
+        assert_eq!(answer.unwrap().unwrap(), 1_u32.into());
+
+        let text = String::from_utf8(trace.finish().unwrap()).unwrap();
+        assert_eq!(
+            text,
+            "  Game 1 is possible, drawing 1 blue, 3 red in all\n  \
+             Game 2 is impossible, since draw 2 (3 green, 20 red) doesn't fit in the bag\n"
+        );
+    }
+
+    #[test]
+    fn generates_games_from_the_bag() {
+        let problem = CubeConundrum::default();
+        let input = problem.generate(&mut Rng::new(0), 100).unwrap();
+        let games: Vec<Game> = parse::lines(&input).unwrap();
+        assert_eq!(games.len(), 100);
+        assert!(games.iter().all(|game| !game.sets.is_empty()));
+
+        let possible = (games.iter())
+            .filter(|game| game.impossible_draw(&problem.bag).is_none())
+            .count();
+        assert!(0 < possible && possible < 100);
+    }
+
+    #[test]
+    fn anonymizes_games_keeping_impossible_draws() {
+        let problem = CubeConundrum::default();
+        let anonymized = problem
+            .anonymize(EXAMPLE, &mut Rng::new(0))
+            .unwrap()
+            .unwrap();
+        assert_ne!(anonymized, EXAMPLE);
+
+        let games: Vec<Game> = parse::lines(&anonymized).unwrap();
+        let originals: Vec<Game> = parse::lines(EXAMPLE).unwrap();
+        for (game, original) in games.iter().zip(&originals) {
+            assert_eq!(game.id, original.id);
+            assert_eq!(
+                game.impossible_draw(&problem.bag).map(|(i, _)| i),
+                original.impossible_draw(&problem.bag).map(|(i, _)| i)
+            );
+        }
+        assert_eq!(problem.part_1(&anonymized), problem.part_1(EXAMPLE));
+
+        // Lines which can't be read are still renumbered, so they can't be read in the same way.
+        let broken = problem.anonymize("Game x: 12 red", &mut Rng::new(0));
+        assert!(regex!(r"^Game x: [1-9]\d red\n$").is_match(&broken.unwrap().unwrap()));
+    }
+
+    #[test]
+    fn solves_the_example() {
+        let mut problem = CubeConundrum::default();
+        assert_eq!(problem.part_1(EXAMPLE).unwrap(), 8_u32.into());
+        assert_eq!(problem.part_2(EXAMPLE).unwrap(), 2286_u32.into());
+
+        let params = [("bag".to_string(), "20 red, 13 green, 15 blue".to_string())];
+        problem.configure(&params.into_iter().collect()).unwrap();
+        assert_eq!(problem.part_1(EXAMPLE).unwrap(), 15_u32.into());
+
+        // The bag only limits which games are possible, not the power of the fewest cubes.
+        let params = [(
+            "bag".to_string(),
+            "12 red, 13 green, 14 blue, 1 purple".to_string(),
+        )];
+        problem.configure(&params.into_iter().collect()).unwrap();
+        assert_eq!(problem.part_2(EXAMPLE).unwrap(), 2286_u32.into());
+
+        let params = [("bag".to_string(), "red".to_string())];
+        assert!(problem.configure(&params.into_iter().collect()).is_err());
+    }
+
+    fn sets() -> impl Strategy<Value = Set> {
+        let colors = prop::sample::select(vec!["red", "green", "blue", "purple"]);
+        prop::collection::btree_map(colors.prop_map(String::from), 1..100_u32, 1..4).prop_map(Set)
+    }
+
+    fn games() -> impl Strategy<Value = Game> {
+        (1..1000_u32, prop::collection::vec(sets(), 1..6)).prop_map(|(id, sets)| Game { id, sets })
+    }
+
+    proptest! {
+        #[test]
+        fn writes_sets_and_games_which_read_back(set in sets(), game in games()) {
+            properties::round_trips(&set)?;
+            properties::round_trips(&game)?;
+        }
+
+        #[test]
+        fn fits_sets_within_themselves(set in sets()) {
+            prop_assert!(set.fits_within(&set));
+        }
+
+        #[test]
+        fn fits_every_draw_within_the_minimal_bag(game in games()) {
+            let bag = game.minimal_bag();
+            prop_assert!(game.sets.iter().all(|s| s.fits_within(&bag)));
+            prop_assert!(bag.fits_within(&game.total_drawn().unwrap()));
+            prop_assert_eq!(game.impossible_draw(&bag), None);
+        }
+
+        #[test]
+        fn solves_generated_games(mut rng in properties::rngs(), size in 1..50_usize) {
+            let problem = CubeConundrum::default();
+            let input = problem.generate(&mut rng, size).unwrap();
+            prop_assert!(problem.part_1(&input).is_ok());
+            prop_assert!(problem.part_2(&input).is_ok());
+        }
     }
 }
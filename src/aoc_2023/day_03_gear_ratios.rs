@@ -1,6 +1,40 @@
-use regex::Regex;
-
-use crate::utils::prelude::*;
+//! # Day 3: Gear Ratios
+//!
+//! ## Part 1
+//!
+//! An engine schematic is a grid of numbers, symbols, and periods. Any number adjacent to a
+//! symbol, even diagonally, is a "part number". For example:
+//!
+//! ```
+//! 467..114..
+//! ...*......
+//! ..35..633.
+//! ......#...
+//! 617*......
+//! .....+.58.
+//! ..592.....
+//! ......755.
+//! ...$.*....
+//! .664.598..
+//! ```
+//!
+//! Every number except 114 and 58 is a part number. Find the sum of all of the part numbers. For
+//! the previous example, the sum is 4361.
+//!
+//! ## Part 2
+//!
+//! A "gear" is any `*` adjacent to exactly two part numbers, and its "gear ratio" is the product
+//! of those numbers. Find the sum of all of the gear ratios. For the previous example, the sum is
+//! 467835.
+
+use std::collections::BTreeMap;
+
+use crate::utils::{
+    anonymize,
+    ansi::paint,
+    grid::{Grid, Point, Span},
+    prelude::*,
+};
 
 pub struct GearRatios;
 
@@ -9,190 +43,358 @@ impl Problem for GearRatios {
         "Day 3: Gear Ratios"
     }
 
-    fn part_1(&self, input: &str) {
-        let globs = load_globs(input);
+    fn day(&self) -> u32 {
+        3
+    }
 
-        let symbols: Vec<Symbol> = globs.iter().filter_map(Glob::symbol).collect();
-        let numbers: Vec<Number> = globs.iter().filter_map(Glob::number).collect();
+    fn part_1(&self, input: &str) -> Result<Answer, SolveError> {
+        let schematic = Grid::from_chars(input);
 
-        let sum: u32 = numbers
-            .iter()
-            .filter(|n| symbols.iter().any(|s| s.bounds.is_adjacent_to(&n.bounds)))
+        let sum = load_numbers(&schematic)?
+            .into_iter()
+            .filter(|n| is_part_number(&schematic, n))
             .map(|n| n.value)
-            .sum();
+            .checked_sum("summing the part numbers")?;
 
-        println!("Sum: {}", sum);
+        Ok(sum.into())
     }
 
-    fn part_2(&self, input: &str) {
-        let globs = load_globs(input);
+    fn part_2(&self, input: &str) -> Result<Answer, SolveError> {
+        let schematic = Grid::from_chars(input);
+        let numbers = load_numbers(&schematic)?;
 
-        let symbols: Vec<Symbol> = globs.iter().filter_map(Glob::symbol).collect();
-        let numbers: Vec<Number> = globs.iter().filter_map(Glob::number).collect();
+        let sum = gear_ratios(&schematic, &numbers)?
+            .into_iter()
+            .map(|gear| gear.ratio)
+            .checked_sum("summing the gear ratios")?;
+
+        Ok(sum.into())
+    }
 
-        let sum: u32 = symbols
-            .iter()
-            .filter(|s| s.value == '*')
-            .map(|s| {
-                numbers
-                    .iter()
-                    .filter(|n| s.bounds.is_adjacent_to(&n.bounds))
-                    .map(|n| n.value)
-                    .collect::<Vec<u32>>()
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Some(generate_schematic(rng, size))
+    }
+
+    /// Renumbers the schematic and swaps its symbols for others, leaving `*` alone so the same
+    /// stars are gears.
+    fn anonymize(&self, input: &str, rng: &mut Rng) -> Option<Result<String, SolveError>> {
+        let others: Vec<char> = SYMBOLS.into_iter().filter(|&c| c != '*').collect();
+        let anonymized = (anonymize::renumber(rng, input).chars())
+            .map(|c| match c != '*' && is_symbol(&c) {
+                true => *rng.choose(&others),
+                false => c,
             })
-            .filter(|nums| nums.len() == 2)
-            .map(|nums| nums.iter().product::<u32>())
-            .sum();
+            .collect();
+        Some(Ok(anonymized))
+    }
 
-        println!("Sum: {}", sum);
+    fn visualize(
+        &self,
+        input: &str,
+        visualizer: &mut dyn Visualizer,
+    ) -> Option<Result<(), SolveError>> {
+        let schematic = Grid::from_chars(input);
+        Some(load_numbers(&schematic).and_then(|numbers| {
+            let gears = gear_ratios(&schematic, &numbers)?;
+            visualizer.emit(&mut || render(input, &schematic, &numbers, &gears));
+            Ok(())
+        }))
     }
 }
 
-fn load_globs(input: &str) -> Vec<Glob> {
-    input
-        .split('\n')
-        .enumerate()
-        .flat_map(Glob::from_line)
+/// A number in the schematic, along with where it is.
+#[derive(Debug, PartialEq)]
+struct Number {
+    value: Total,
+    span: Span,
+}
+
+fn load_numbers(schematic: &Grid<char>) -> Result<Vec<Number>, SolveError> {
+    (schematic.spans(char::is_ascii_digit).into_iter())
+        .map(|span| {
+            let digits: String = span.points().filter_map(|p| schematic.get(p)).collect();
+            Ok(Number {
+                value: (digits.parse())
+                    .map_err(|_| SolveError::Overflow("reading a part number"))?,
+                span,
+            })
+        })
         .collect()
 }
 
-enum Glob {
-    Number(Number),
-    Symbol(Symbol),
+fn is_symbol(c: &char) -> bool {
+    !c.is_ascii_digit() && *c != '.' && !c.is_whitespace()
 }
 
-const MATCH_NUMBER: &'static str = r"\d+";
-const MATCH_SYMBOL: &'static str = r"[\*\$\+\-\=\/\@\%\&\#]";
-
-impl Glob {
-    pub fn from_line((y, line): (usize, &str)) -> Vec<Glob> {
-        let mut globs = vec![];
-        if let Ok(re) = Regex::new(MATCH_NUMBER) {
-            let mut numbers = re
-                .find_iter(line)
-                .map(|c| {
-                    Glob::Number(Number {
-                        value: c.as_str().parse().unwrap(),
-                        bounds: Bounds {
-                            x: c.start(),
-                            l: c.len(),
-                            y,
-                        },
-                    })
-                })
-                .collect::<Vec<Glob>>();
-            globs.append(&mut numbers);
-        }
-        if let Ok(re) = Regex::new(MATCH_SYMBOL) {
-            let mut symbols = re
-                .find_iter(line)
-                .map(|c| {
-                    Glob::Symbol(Symbol {
-                        value: c.as_str().chars().next().unwrap(),
-                        bounds: Bounds {
-                            x: c.start(),
-                            l: c.len(),
-                            y,
-                        },
-                    })
-                })
-                .collect::<Vec<Glob>>();
-            globs.append(&mut symbols);
-        }
-        globs
-    }
+fn is_part_number(schematic: &Grid<char>, number: &Number) -> bool {
+    (schematic.span_neighbours(number.span)).any(|p| schematic.get(p).is_some_and(is_symbol))
+}
 
-    pub fn number(&self) -> Option<Number> {
-        match self {
-            Glob::Number(value) => Some(*value),
-            _ => None,
+/// Finds every `*` next to exactly two numbers, along with those numbers.
+fn gears<'a>(schematic: &Grid<char>, numbers: &'a [Number]) -> BTreeMap<Point, [&'a Number; 2]> {
+    let mut stars: BTreeMap<Point, Vec<&Number>> = BTreeMap::new();
+    for number in numbers {
+        for point in schematic.span_neighbours(number.span) {
+            if schematic.get(point) == Some(&'*') {
+                stars.entry(point).or_default().push(number);
+            }
         }
     }
 
-    pub fn symbol(&self) -> Option<Symbol> {
-        match self {
-            Glob::Symbol(value) => Some(*value),
-            _ => None,
-        }
-    }
+    (stars.into_iter())
+        .filter_map(|(point, nums)| Some((point, <[&Number; 2]>::try_from(nums).ok()?)))
+        .collect()
 }
 
-#[derive(Clone, Copy)]
-struct Number {
-    value: u32,
-    bounds: Bounds,
+/// A gear, along with its two numbers and their product.
+struct Gear<'a> {
+    point: Point,
+    numbers: [&'a Number; 2],
+    ratio: Total,
 }
 
-#[derive(Clone, Copy)]
-struct Symbol {
-    value: char,
-    bounds: Bounds,
+/// Finds every gear and works out its ratio.
+fn gear_ratios<'a>(
+    schematic: &Grid<char>,
+    numbers: &'a [Number],
+) -> Result<Vec<Gear<'a>>, SolveError> {
+    (gears(schematic, numbers).into_iter())
+        .map(|(point, [a, b])| {
+            let ratio = num::mul(&a.value, &b.value, "multiplying a gear ratio")?;
+            Ok(Gear {
+                point,
+                numbers: [a, b],
+                ratio,
+            })
+        })
+        .collect()
 }
 
-#[derive(Clone, Copy)]
-struct Bounds {
-    x: usize,
-    y: usize,
-    l: usize,
+const SYMBOLS: [char; 10] = ['*', '#', '+', '$', '/', '@', '=', '%', '&', '-'];
+
+/// Generates a square schematic with the given width, scattered with numbers and symbols.
+fn generate_schematic(rng: &mut Rng, size: usize) -> String {
+    let mut schematic = String::new();
+    for _ in 0..size {
+        let mut row = String::new();
+        while row.len() < size {
+            let room = size - row.len();
+            if rng.chance(0.12) {
+                let len = rng.range(1..=3.min(room as u64)) as u32;
+                row += &rng
+                    .range(10_u64.pow(len - 1)..=10_u64.pow(len) - 1)
+                    .to_string();
+                if row.len() < size {
+                    row.push('.');
+                }
+            } else if rng.chance(0.06) {
+                row.push(*rng.choose(&SYMBOLS));
+            } else {
+                row.push('.');
+            }
+        }
+        schematic += &row;
+        schematic.push('\n');
+    }
+    schematic
 }
 
-impl Bounds {
-    pub fn is_adjacent_to(&self, other: &Self) -> bool {
-        (self.x - other.l <= other.x && other.x <= self.x + self.l)
-            && (self.y - 1 <= other.y && other.y <= self.y + 1)
+/// Draws the schematic with part numbers highlighted, other numbers dimmed, and gears marked,
+/// captioned with the ratio of each gear.
+fn render(input: &str, schematic: &Grid<char>, numbers: &[Number], gears: &[Gear]) -> Frame {
+    let mut frame = Frame::from_text(input);
+    for point in schematic.points() {
+        if schematic.get(point).is_some_and(is_symbol) {
+            frame.paint(point, Color::Cyan);
+        }
+    }
+    for number in numbers {
+        let color = match is_part_number(schematic, number) {
+            true => Color::BoldGreen,
+            false => Color::Dim,
+        };
+        for point in number.span.points() {
+            frame.paint(point, color);
+        }
+    }
+    for gear in gears {
+        frame.paint(gear.point, Color::BoldYellow);
+    }
+
+    let legend = [
+        (Color::BoldGreen, "part number"),
+        (Color::Dim, "other number"),
+        (Color::Cyan, "symbol"),
+        (Color::BoldYellow, "gear"),
+    ];
+    let mut caption: Vec<String> = vec![(legend.iter())
+        .map(|(color, name)| paint(&color.ansi().unwrap_or_default(), name))
+        .collect::<Vec<_>>()
+        .join("  ")];
+    for gear in gears {
+        let [a, b] = gear.numbers;
+        caption.push(format!(
+            "Gear at line {}, column {}: {} × {} = {}",
+            gear.point.y + 1,
+            gear.point.x + 1,
+            a.value,
+            b.value,
+            gear.ratio
+        ));
     }
+
+    frame.with_caption(caption.join("\n"))
 }
 
 #[cfg(test)]
 mod tests {
+    use proptest::{prop_assert_eq, proptest};
+
+    use crate::utils::{properties, visualizer::Recording};
+
     use super::*;
 
-    #[test]
-    fn tests_bounds_adjacency() {
-        assert!(Bounds { x: 1, y: 1, l: 1 }.is_adjacent_to(&Bounds { x: 0, y: 1, l: 1 }));
-        assert!(Bounds { x: 1, y: 1, l: 1 }.is_adjacent_to(&Bounds { x: 1, y: 1, l: 1 }));
-        assert!(Bounds { x: 1, y: 1, l: 1 }.is_adjacent_to(&Bounds { x: 2, y: 1, l: 1 }));
-        assert!(!Bounds { x: 1, y: 1, l: 1 }.is_adjacent_to(&Bounds { x: 3, y: 1, l: 1 }));
+    const EXAMPLE: &str = "467..114..\n...*......\n..35..633.\n......#...\n617*......\n\
+                           .....+.58.\n..592.....\n......755.\n...$.*....\n.664.598..\n";
+
+    /// Gets where the schematic's numbers are, which of them are part numbers, and where its gears
+    /// are.
+    fn shape(input: &str) -> (Vec<Span>, Vec<bool>, Vec<Point>) {
+        let schematic = Grid::from_chars(input);
+        let numbers = load_numbers(&schematic).unwrap();
+        let parts: Vec<bool> = (numbers.iter())
+            .map(|n| is_part_number(&schematic, n))
+            .collect();
+        let spans: Vec<Span> = numbers.iter().map(|n| n.span).collect();
+        let gears: Vec<Point> = gears(&schematic, &numbers).into_keys().collect();
+        (spans, parts, gears)
     }
 
     #[test]
-    fn creates_numbers_from_strings() {
-        let globs = Glob::from_line((0, ""));
-
-        assert_eq!(0, globs.len());
-
-        let globs = Glob::from_line((67, "12....345..*/"));
+    fn loads_numbers_from_schematics() {
+        let numbers = load_numbers(&Grid::from_chars("12....345..*/")).unwrap();
+        assert_eq!(
+            numbers,
+            vec![
+                Number {
+                    value: Total::from(12_u32),
+                    span: Span { x: 0, y: 0, len: 2 }
+                },
+                Number {
+                    value: Total::from(345_u32),
+                    span: Span { x: 6, y: 0, len: 3 }
+                },
+            ]
+        );
+    }
 
-        assert_eq!(4, globs.len());
-        assert_eq!(2, globs.iter().filter_map(Glob::number).count());
-        assert_eq!(2, globs.iter().filter_map(Glob::symbol).count());
+    #[test]
+    fn finds_part_numbers_at_the_edges() {
+        assert_eq!(GearRatios.part_1("1*\n").unwrap(), 1_u32.into());
+        assert_eq!(GearRatios.part_1("*.\n.2\n").unwrap(), 2_u32.into());
+        assert_eq!(GearRatios.part_1("..3\n$..\n").unwrap(), 0_u32.into());
+    }
 
-        let number_1 = globs[0].number().unwrap();
+    #[test]
+    fn rejects_part_numbers_which_overflow() {
+        let schematic = format!("{}*\n", "9".repeat(40));
+        assert_eq!(
+            GearRatios.part_1(&schematic).is_err(),
+            !cfg!(feature = "bigint")
+        );
+    }
 
-        assert_eq!(12, number_1.value);
-        assert_eq!(0, number_1.bounds.x);
-        assert_eq!(67, number_1.bounds.y);
-        assert_eq!(2, number_1.bounds.l);
+    #[test]
+    fn finds_gears_next_to_exactly_two_numbers() {
+        let schematic = Grid::from_chars("1*2\n.*.\n3*4\n");
+        let numbers = load_numbers(&schematic).unwrap();
+        let gears = gears(&schematic, &numbers);
+        let values = |p| gears.get(&p).map(|[a, b]| (&a.value, &b.value));
+        let total = Total::from;
+
+        // The middle star touches all four numbers, so it isn't a gear.
+        assert_eq!(gears.len(), 2);
+        assert_eq!(
+            values(Point::new(1, 0)),
+            Some((&total(1_u32), &total(2_u32)))
+        );
+        assert_eq!(
+            values(Point::new(1, 2)),
+            Some((&total(3_u32), &total(4_u32)))
+        );
+    }
 
-        let number_2 = globs[1].number().unwrap();
+    #[test]
+    fn renders_parts_and_gears() {
+        let mut recording = Recording::default();
+        let input = "467..114..\n...*......\n..35......\n";
+        GearRatios
+            .visualize(input, &mut recording)
+            .unwrap()
+            .unwrap();
+        let [frame] = <[Frame; 1]>::try_from(recording.frames).unwrap();
+        let color = |x, y| frame.grid.get(Point::new(x, y)).map(|cell| cell.color);
+        assert_eq!(color(0, 0), Some(Color::BoldGreen));
+        assert_eq!(color(5, 0), Some(Color::Dim));
+        assert_eq!(color(3, 1), Some(Color::BoldYellow));
+        assert_eq!(color(0, 1), Some(Color::Default));
+        assert!(frame.caption.unwrap().contains("467 × 35 = 16345"));
+    }
 
-        assert_eq!(345, number_2.value);
-        assert_eq!(6, number_2.bounds.x);
-        assert_eq!(67, number_2.bounds.y);
-        assert_eq!(3, number_2.bounds.l);
+    #[test]
+    fn generates_square_schematics() {
+        let input = GearRatios.generate(&mut Rng::new(0), 40).unwrap();
+        let schematic = Grid::from_chars(&input);
+        assert_eq!((schematic.width(), schematic.height()), (40, 40));
+
+        let numbers = load_numbers(&schematic).unwrap();
+        assert!(numbers.iter().any(|n| is_part_number(&schematic, n)));
+        assert!(numbers.iter().any(|n| !is_part_number(&schematic, n)));
+        assert!(!gears(&schematic, &numbers).is_empty());
+    }
 
-        let symbol_1 = globs[2].symbol().unwrap();
+    #[test]
+    fn anonymizes_schematics_keeping_parts_and_gears() {
+        let anonymized = GearRatios
+            .anonymize(EXAMPLE, &mut Rng::new(0))
+            .unwrap()
+            .unwrap();
+        assert_ne!(anonymized, EXAMPLE);
+        assert_eq!(shape(&anonymized), shape(EXAMPLE));
+    }
 
-        assert_eq!('*', symbol_1.value);
-        assert_eq!(11, symbol_1.bounds.x);
-        assert_eq!(67, symbol_1.bounds.y);
-        assert_eq!(1, symbol_1.bounds.l);
+    #[test]
+    fn solves_the_example() {
+        assert_eq!(GearRatios.part_1(EXAMPLE).unwrap(), 4361_u32.into());
+        assert_eq!(GearRatios.part_2(EXAMPLE).unwrap(), 467835_u32.into());
+    }
 
-        let symbol_2 = globs[3].symbol().unwrap();
+    proptest! {
+        #[test]
+        fn anonymizes_generated_schematics_keeping_parts_and_gears(
+            mut rng in properties::rngs(),
+            size in 1..30_usize,
+        ) {
+            let input = GearRatios.generate(&mut rng, size).unwrap();
+            let anonymized = GearRatios.anonymize(&input, &mut rng).unwrap().unwrap();
+            prop_assert_eq!(shape(&anonymized), shape(&input));
+        }
 
-        assert_eq!('/', symbol_2.value);
-        assert_eq!(12, symbol_2.bounds.x);
-        assert_eq!(67, symbol_2.bounds.y);
-        assert_eq!(1, symbol_2.bounds.l);
+        #[test]
+        fn solves_schematics_the_same_with_a_border(
+            mut rng in properties::rngs(),
+            size in 1..30_usize,
+        ) {
+            let input = GearRatios.generate(&mut rng, size).unwrap();
+            let border = ".".repeat(size + 2);
+            let bordered: String = [border.clone()]
+                .into_iter()
+                .chain(input.lines().map(|line| format!(".{}.", line)))
+                .chain([border])
+                .map(|line| line + "\n")
+                .collect();
+            prop_assert_eq!(GearRatios.part_1(&bordered), GearRatios.part_1(&input));
+            prop_assert_eq!(GearRatios.part_2(&bordered), GearRatios.part_2(&input));
+        }
     }
 }
@@ -1,6 +1,10 @@
-use regex::Regex;
+use std::{collections::VecDeque, fmt::Display, str::FromStr};
 
-use crate::utils::prelude::*;
+use crate::utils::{
+    anonymize,
+    minimize::{self, Reducer, Split},
+    prelude::*,
+};
 
 pub struct Scratchcards;
 
@@ -9,129 +13,594 @@ impl Problem for Scratchcards {
         "Day 4: Scratchcards"
     }
 
-    fn part_1(&self, input: &str) {
-        let sum: u32 = load_cards(input).iter().map(Card::points).sum();
-        println!("Sum: {}", sum);
+    fn day(&self) -> u32 {
+        4
     }
 
-    fn part_2(&self, input: &str) {
-        let mut cards = load_cards(input);
+    fn part_1(&self, input: &str) -> Result<Answer, SolveError> {
+        self.solve_reader(Part::One, &mut input.as_bytes())
+    }
+
+    fn part_2(&self, input: &str) -> Result<Answer, SolveError> {
+        self.solve_reader(Part::Two, &mut input.as_bytes())
+    }
+
+    fn is_streaming(&self, _part: Part) -> bool {
+        true
+    }
+
+    fn solve_lines(&self, part: Part, lines: &mut Lines) -> Result<Answer, SolveError> {
+        self.sum_cards(part, lines, &mut Silent)
+    }
+
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        let width = size.to_string().len();
+        Some(
+            (1..=size)
+                .map(|id| generate_card(rng, id, width) + "\n")
+                .collect(),
+        )
+    }
 
-        for i in 0..cards.len() {
-            let _cards = cards.clone();
-            let cursor = _cards.get(i).unwrap();
-            for j in (i + 1)..(cursor.matches() as usize + i + 1) {
-                cards.get_mut(j).and_then(|c| Some(c.add(cursor.count)));
+    fn implementations(&self, part: Part) -> Vec<Implementation<'_>> {
+        let mut implementations = vec![Implementation::new("cascade", move |input| {
+            self.solve(part, input)
+        })];
+        if part == Part::Two {
+            implementations.push(Implementation::new("copy table", copy_table));
+        }
+        implementations
+    }
+
+    /// Swaps every number for another with as many digits, the same way on every card, so that
+    /// each card still has the same matches.
+    fn anonymize(&self, input: &str, rng: &mut Rng) -> Option<Result<String, SolveError>> {
+        let mut swaps: Vec<u64> = (0..u64::from(NumberSet::LIMIT)).collect();
+        for digits in [1..10, 10..100, 100..swaps.len()] {
+            rng.shuffle(&mut swaps[digits]);
+        }
+
+        let swap = |rng: &mut Rng, number: &str| match number.parse::<usize>() {
+            Ok(n) if n < swaps.len() => swaps[n].to_string(),
+            _ if number.len() == 3 => rng.range(u64::from(NumberSet::LIMIT)..=999).to_string(),
+            _ => anonymize::number_like(rng, number),
+        };
+        let anonymized = (input.lines())
+            .map(|line| match line.split_once(':') {
+                Some((card, numbers)) => {
+                    let numbers = regex!(r"\d+")
+                        .replace_all(numbers, |caps: &regex::Captures| swap(rng, &caps[0]));
+                    format!("{}:{}\n", card, numbers)
+                }
+                None => anonymize::renumber(rng, line) + "\n",
+            })
+            .collect();
+        Some(Ok(anonymized))
+    }
+
+    fn explain(
+        &self,
+        part: Part,
+        input: &str,
+        explainer: &mut dyn Explainer,
+    ) -> Option<Result<Answer, SolveError>> {
+        let mut lines = input.lines().map(|line| Ok(line.to_string()));
+        Some(self.sum_cards(part, &mut lines, explainer))
+    }
+
+    /// Lists how many copies of each card end up being won for the query `copies`.
+    fn query(&self, input: &str, query: &str) -> Option<Result<Vec<String>, SolveError>> {
+        Some(match query.trim() {
+            "copies" => self.copy_counts(input).map(|counts| {
+                (counts.into_iter())
+                    .map(|(id, count)| format!("Card {}: {} cop(ies)", id, count))
+                    .collect()
+            }),
+            _ => Err(SolveError::Query(format!(
+                "{:?} isn't a query this problem answers. Try `copies`",
+                query
+            ))),
+        })
+    }
+
+    fn reducer(&self) -> &dyn Reducer {
+        self
+    }
+}
+
+/// Removes whole cards, and then single numbers from either side of each card while keeping its
+/// ID and the bar between the sides.
+impl Reducer for Scratchcards {
+    fn split<'a>(&self, input: &'a str, level: usize) -> Option<Split<'a>> {
+        let cards: Vec<(&str, Option<Vec<&str>>)> = (input.lines())
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| match line.split_once(':') {
+                Some((card, numbers)) => (card, Some(numbers.split_whitespace().collect())),
+                None => (line, None),
+            })
+            .collect();
+        let write = |(card, numbers): (&str, Option<Vec<&str>>)| match numbers {
+            Some(numbers) => format!("{}: {}", card, numbers.join(" ")),
+            None => card.to_string(),
+        };
+        match level {
+            0 => Some(Split::new(cards.len(), move |keep| {
+                minimize::join_lines(minimize::kept(&cards, keep).cloned().map(write))
+            })),
+            1 => {
+                let count = (cards
+                    .iter()
+                    .flat_map(|(_, numbers)| numbers.iter().flatten()))
+                .filter(|n| **n != "|")
+                .count();
+                Some(Split::new(count, move |keep| {
+                    let mut keep = keep.iter();
+                    minimize::join_lines((cards.iter()).map(|(card, numbers)| {
+                        let numbers = numbers.as_ref().map(|numbers| {
+                            (numbers.iter())
+                                .filter(|n| **n == "|" || *keep.next().unwrap_or(&true))
+                                .copied()
+                                .collect()
+                        });
+                        write((card, numbers))
+                    }))
+                }))
             }
+            _ => None,
         }
+    }
+}
 
-        let sum: u32 = cards.iter().map(|c| c.count).sum();
-        println!("Sum: {}", sum);
+impl Scratchcards {
+    /// Sums the points or copies of each card, explaining what each one matched, and for copies
+    /// which card ends up with the most, picking the first of any ties.
+    fn sum_cards(
+        &self,
+        part: Part,
+        lines: &mut Lines,
+        explainer: &mut dyn Explainer,
+    ) -> Result<Answer, SolveError> {
+        let mut cascade = Cascade::default();
+        let mut sum = Total::from(0_u32);
+        let mut most: Option<(u32, Total)> = None;
+        for card in parse::stream::<Card>(lines) {
+            let card = card?;
+            let (value, computing) = match part {
+                Part::One => (card.points()?, "summing the points"),
+                Part::Two => (cascade.push(&card)?, COUNTING),
+            };
+            explainer.explain(&mut || {
+                let matches: Vec<String> = (card.numbers.intersection(&card.winning_numbers))
+                    .iter()
+                    .map(|n| n.to_string())
+                    .collect();
+                let matched = match matches.is_empty() {
+                    true => format!("Card {} has no matches", card.id),
+                    false => format!("Card {} matches {}", card.id, matches.join(", ")),
+                };
+                match part {
+                    Part::One => format!("{}, so it's worth {} point(s)", matched, value),
+                    Part::Two => format!("{}, and ends up with {} cop(ies)", matched, value),
+                }
+            });
+            sum = num::add(&sum, &value, computing)?;
+            if part == Part::Two && most.as_ref().is_none_or(|(_, count)| value > *count) {
+                most = Some((card.id, value));
+            }
+        }
+        if let Some((id, count)) = most {
+            explainer
+                .explain(&mut || format!("Card {} ends up with the most copies, {}", id, count));
+        }
+        Ok(sum.into())
+    }
+
+    /// Gets the ID of each card along with how many copies of it end up being won.
+    pub fn copy_counts(&self, input: &str) -> Result<Vec<(u32, Total)>, SolveError> {
+        let mut cascade = Cascade::default();
+        (parse::lines::<Card>(input)?.iter())
+            .map(|card| Ok((card.id, cascade.push(card)?)))
+            .collect()
     }
 }
 
-fn load_cards(input: &str) -> Vec<Card> {
-    input
-        .split('\n')
-        .filter(|l| l.len() != 0)
-        .map(Card::from)
-        .collect()
+/// Generates a card with ten winning numbers and twenty-five numbers, padded into columns.
+///
+/// Most cards match nothing, so the copies won stay small however many cards there are.
+fn generate_card(rng: &mut Rng, id: usize, width: usize) -> String {
+    let pool = rng.distinct(1..=99, 35);
+    let (winning, rest) = pool.split_at(10);
+    let matches = match rng.chance(0.85) {
+        true => 0,
+        false => rng.range(1..=10) as usize,
+    };
+
+    let mut numbers = [&winning[..matches], &rest[..25 - matches]].concat();
+    rng.shuffle(&mut numbers);
+    let column = |numbers: &[u64]| {
+        (numbers.iter())
+            .map(|n| format!("{:>2}", n))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    format!(
+        "Card {:>width$}: {} | {}",
+        id,
+        column(winning),
+        column(&numbers)
+    )
 }
 
-#[derive(Debug, PartialEq)]
+/// Counts the scratchcards by keeping a table of every card's copies, adding each card's copies
+/// to every card it wins copies of.
+fn copy_table(input: &str) -> Result<Answer, SolveError> {
+    let cards: Vec<Card> = parse::lines(input)?;
+    let mut copies = vec![Total::from(1_u32); cards.len()];
+    for (i, card) in cards.iter().enumerate() {
+        let (before, after) = copies.split_at_mut(i + 1);
+        for copy in after.iter_mut().take(card.matches() as usize) {
+            *copy = num::add(copy, &before[i], COUNTING)?;
+        }
+    }
+    Ok(copies.into_iter().checked_sum(COUNTING)?.into())
+}
+
+const COUNTING: &str = "counting the scratchcards";
+
+/// Counts how many copies of each card are won, including the original, one card at a time.
+///
+/// Each card adds its copies to a run of the cards after it, so rather than visiting every card
+/// in that run, the copies are added to a running total and taken back off where the run ends.
+/// Runs are never longer than [`NumberSet::LIMIT`], so only that many cards ahead are tracked.
+#[derive(Default)]
+struct Cascade {
+    running: Total,
+    run_ends: VecDeque<Total>,
+}
+
+impl Cascade {
+    /// Gets how many copies of the next card are won.
+    pub fn push(&mut self, card: &Card) -> Result<Total, SolveError> {
+        if let Some(ended) = self.run_ends.pop_front() {
+            self.running -= &ended;
+        }
+        let copies = num::add(&self.running, &Total::from(1_u32), COUNTING)?;
+
+        let matches = card.matches() as usize;
+        if matches > 0 {
+            if self.run_ends.len() <= matches {
+                self.run_ends.resize(matches + 1, Total::from(0_u32));
+            }
+            self.running = num::add(&self.running, &copies, COUNTING)?;
+            self.run_ends[matches] = num::add(&self.run_ends[matches], &copies, COUNTING)?;
+        }
+
+        Ok(copies)
+    }
+}
+
+#[derive(Debug, PartialEq, FromInput)]
+#[pattern(r"Card\s+(?P<id>\d+):(?P<winning_numbers>[\d\s]+)\|(?P<numbers>[\d\s]+)")]
 struct Card {
     pub id: u32,
-    pub numbers: Vec<u32>,
-    pub winning_numbers: Vec<u32>,
-    pub count: u32,
+    pub numbers: NumberSet,
+    pub winning_numbers: NumberSet,
 }
 
 impl Card {
     pub fn matches(&self) -> u32 {
-        (self.numbers.iter())
-            .filter(|x| self.winning_numbers.contains(x))
-            .count() as u32
+        self.numbers.intersection(&self.winning_numbers).len()
     }
 
-    pub fn points(&self) -> u32 {
+    pub fn points(&self) -> Result<Total, SolveError> {
         match self.matches() {
-            0 => 0,
-            x => 2_u32.pow(x - 1),
+            0 => Ok(Total::from(0_u32)),
+            x => num::pow(Total::from(2_u32), x as usize - 1, "scoring a card"),
         }
     }
+}
 
-    pub fn add(&mut self, quantity: u32) {
-        self.count += quantity;
+impl Display for Card {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Card {}: {} | {}",
+            self.id, self.winning_numbers, self.numbers
+        )
     }
 }
 
-impl Clone for Card {
-    fn clone(&self) -> Self {
-        Self {
-            id: self.id.clone(),
-            numbers: self.numbers.clone(),
-            winning_numbers: self.winning_numbers.clone(),
-            count: self.count.clone(),
-        }
+/// A set of the numbers below [`NumberSet::LIMIT`], stored as one bit per number.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct NumberSet(u128);
+
+impl NumberSet {
+    pub const LIMIT: u32 = u128::BITS;
+
+    pub fn intersection(&self, other: &NumberSet) -> NumberSet {
+        NumberSet(self.0 & other.0)
+    }
+
+    pub fn len(&self) -> u32 {
+        self.0.count_ones()
+    }
+
+    /// Iterates over the numbers in the set, from smallest to largest.
+    pub fn iter(&self) -> impl Iterator<Item = u32> + '_ {
+        (0..Self::LIMIT).filter(|n| self.0 & (1 << n) != 0)
     }
 }
 
-const MATCH_CARD: &'static str = r"Card\s+(\d+):\s+((?:\d+\s+)+)\|((?:\s+\d+)+)";
-
-impl From<&str> for Card {
-    fn from(value: &str) -> Self {
-        let cap = (Regex::new(MATCH_CARD).ok())
-            .and_then(|re| re.captures(value))
-            .expect("Match was not found.");
-
-        Self {
-            id: (cap.get(1))
-                .and_then(|id| id.as_str().parse().ok())
-                .expect("ID was not a number."),
-            winning_numbers: (cap.get(2))
-                .expect("Winning numbers were not found.")
-                .as_str()
-                .replace("  ", " ")
-                .split(" ")
-                .filter_map(|x| x.parse().ok())
-                .collect(),
-            numbers: (cap.get(3))
-                .expect("Numbers were not found.")
-                .as_str()
-                .replace("  ", " ")
-                .split(" ")
-                .filter_map(|x| x.parse().ok())
-                .collect(),
-            count: 1,
+impl Display for NumberSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let numbers: Vec<String> = self.iter().map(|n| n.to_string()).collect();
+        write!(f, "{}", numbers.join(" "))
+    }
+}
+
+impl FromStr for NumberSet {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut set = NumberSet::default();
+        for number in value.split_whitespace() {
+            let n: u32 = number.parse().map_err(|err| format!("{}", err))?;
+            if n >= Self::LIMIT {
+                return Err(format!("{} is not below {}", n, Self::LIMIT));
+            }
+            set.0 |= 1 << n;
         }
+        Ok(set)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use proptest::{
+        prelude::{any, Strategy},
+        prop_assert, prop_assert_eq, proptest,
+    };
+
+    use crate::utils::{explain::Trace, properties};
+
     use super::*;
 
+    const EXAMPLE: &str = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11
+";
+
     #[test]
     fn creates_cards_from_strings() {
         assert_eq!(
-            Card::from("Card 123: 1 2 3 | 4 5 6"),
-            Card {
+            "Card 123: 1 2 3 | 4 5 6".parse(),
+            Ok(Card {
                 id: 123,
-                numbers: vec![4, 5, 6],
-                winning_numbers: vec![1, 2, 3],
-                count: 1,
-            }
+                numbers: "4 5 6".parse().unwrap(),
+                winning_numbers: "1 2 3".parse().unwrap(),
+            })
         );
     }
 
+    #[test]
+    fn rejects_malformed_cards() {
+        assert!("Card 1: 1 2 3".parse::<Card>().is_err());
+        assert!("Card 1: 1 2 | 3 x".parse::<Card>().is_err());
+        assert!("Card: 1 | 2".parse::<Card>().is_err());
+        assert!("Card 1: 1 | 128".parse::<Card>().is_err());
+    }
+
+    fn card(line: &str) -> Card {
+        line.parse().unwrap()
+    }
+
     #[test]
     fn calculates_points() {
-        assert_eq!(0, Card::from("Card 1: 1 | 0").points());
-        assert_eq!(1, Card::from("Card 1: 1 | 1").points());
-        assert_eq!(2, Card::from("Card 1: 1 2 | 1 2").points());
-        assert_eq!(4, Card::from("Card 1: 1 2 3 | 1 2 3").points());
-        assert_eq!(8, Card::from("Card 1: 1 2 3 4 | 1 2 3 4").points());
-        assert_eq!(16, Card::from("Card 1: 1 2 3 4 5 | 1 2 3 4 5").points());
-        assert_eq!(32, Card::from("Card 1: 1 2 3 4 5 6 | 1 2 3 4 5 6").points());
+        assert_eq!(Ok(Total::from(0_u32)), card("Card 1: 1 | 0").points());
+        assert_eq!(Ok(Total::from(1_u32)), card("Card 1: 1 | 1").points());
+        assert_eq!(Ok(Total::from(2_u32)), card("Card 1: 1 2 | 1 2").points());
+        assert_eq!(
+            Ok(Total::from(4_u32)),
+            card("Card 1: 1 2 3 | 1 2 3").points()
+        );
+        assert_eq!(
+            Ok(Total::from(8_u32)),
+            card("Card 1: 1 2 3 4 | 1 2 3 4").points()
+        );
+        assert_eq!(
+            Ok(Total::from(16_u32)),
+            card("Card 1: 1 2 3 4 5 | 1 2 3 4 5").points()
+        );
+        assert_eq!(
+            Ok(Total::from(32_u32)),
+            card("Card 1: 1 2 3 4 5 6 | 1 2 3 4 5 6").points()
+        );
+    }
+
+    #[test]
+    fn scores_cards_without_overflowing() {
+        let numbers: Vec<String> = (0..70).map(|n| n.to_string()).collect();
+        let line = format!("Card 1: {0} | {0}", numbers.join(" "));
+        assert_eq!(card(&line).points().is_err(), !cfg!(feature = "bigint"));
+    }
+
+    #[test]
+    fn matches_numbers_in_both_sets() {
+        assert_eq!(card("Card 1: 0 1 127 | 127 0 5").matches(), 2);
+        assert_eq!(card("Card 1: 1 1 | 1").matches(), 1);
+    }
+
+    #[test]
+    fn counts_copies_won() {
+        let counts: Vec<Total> = [1_u32, 2, 4, 8, 14, 1].map(Total::from).into();
+        assert_eq!(
+            Scratchcards.copy_counts(EXAMPLE).unwrap(),
+            (1..=6).zip(counts).collect::<Vec<_>>()
+        );
+
+        // Copies of cards past the end of the table are never won.
+        let counts = Scratchcards.copy_counts("Card 1: 1 2 | 1 2\nCard 2: 3 | 3\n");
+        assert_eq!(
+            counts.unwrap(),
+            vec![(1, Total::from(1_u32)), (2, Total::from(2_u32))]
+        );
+    }
+
+    #[test]
+    fn answers_queries() {
+        let answer = Scratchcards.query("Card 1: 1 2 | 1 2\nCard 2: 3 | 3\n", "copies");
+        assert_eq!(
+            answer,
+            Some(Ok(vec![
+                "Card 1: 1 cop(ies)".to_string(),
+                "Card 2: 2 cop(ies)".to_string(),
+            ]))
+        );
+        assert!(matches!(
+            Scratchcards.query(EXAMPLE, "points"),
+            Some(Err(SolveError::Query(_)))
+        ));
+    }
+
+    #[test]
+    fn streams_cards() {
+        let mut lines = EXAMPLE.lines().map(|line| Ok(line.to_string()));
+        assert_eq!(
+            Scratchcards.solve_lines(Part::Two, &mut lines).unwrap(),
+            30_u32.into()
+        );
+
+        let mut lines = ["Card 1: 1 | 1", "Card 2: x"]
+            .map(|line| Ok(line.to_string()))
+            .into_iter();
+        assert!(matches!(
+            Scratchcards.solve_lines(Part::One, &mut lines),
+            Err(SolveError::ParseLine(2, _))
+        ));
+    }
+
+    #[test]
+    fn explains_each_card() {
+        let input = "Card 1: 1 5 9 | 9 1 2\nCard 2: 3 | 4\n";
+        for (part, answer, lines) in [
+            (
+                Part::One,
+                2_u32,
+                "  Card 1 matches 1, 9, so it's worth 2 point(s)\n  \
+                 Card 2 has no matches, so it's worth 0 point(s)\n",
+            ),
+            (
+                Part::Two,
+                3_u32,
+                "  Card 1 matches 1, 9, and ends up with 1 cop(ies)\n  \
+                 Card 2 has no matches, and ends up with 2 cop(ies)\n  \
+                 Card 2 ends up with the most copies, 2\n",
+            ),
+        ] {
+            let mut trace = Trace::new(vec![]);
+            let result = Scratchcards.explain(part, input, &mut trace);
+            assert_eq!(result.unwrap().unwrap(), answer.into());
+            assert_eq!(String::from_utf8(trace.finish().unwrap()).unwrap(), lines);
+        }
+    }
+
+    #[test]
+    fn generates_cards_which_can_be_counted() {
+        let input = Scratchcards.generate(&mut Rng::new(0), 1000).unwrap();
+        let cards: Vec<Card> = parse::lines(&input).unwrap();
+        assert_eq!(cards.len(), 1000);
+        assert!(input.starts_with("Card    1: "));
+        assert!(cards.iter().all(|card| card.winning_numbers.len() == 10));
+        assert!(cards.iter().any(|card| card.matches() > 0));
+        assert!(Scratchcards.part_2(&input).is_ok());
+    }
+
+    #[test]
+    fn anonymizes_cards_keeping_their_matches() {
+        let anonymized = Scratchcards
+            .anonymize(EXAMPLE, &mut Rng::new(0))
+            .unwrap()
+            .unwrap();
+        assert_ne!(anonymized, EXAMPLE);
+        assert_eq!(anonymized.len(), EXAMPLE.len());
+
+        let matches = |input: &str| -> Vec<(u32, u32)> {
+            (parse::lines::<Card>(input).unwrap().iter())
+                .map(|card| (card.id, card.matches()))
+                .collect()
+        };
+        assert_eq!(matches(&anonymized), matches(EXAMPLE));
+
+        let broken = Scratchcards.anonymize("Card 1: 1 | 200\n", &mut Rng::new(0));
+        assert!(broken.unwrap().unwrap().parse::<Card>().is_err());
+    }
+
+    #[test]
+    fn agrees_with_the_copy_table() {
+        let generated = Scratchcards.generate(&mut Rng::new(0), 500).unwrap();
+        for input in [EXAMPLE, &generated] {
+            let results: Vec<_> = (Scratchcards.implementations(Part::Two).iter())
+                .map(|implementation| implementation.solve(input))
+                .collect();
+            assert_eq!(results.len(), 2);
+            assert_eq!(results[0], results[1]);
+        }
+        assert_eq!(Scratchcards.implementations(Part::One).len(), 1);
+    }
+
+    #[test]
+    fn solves_the_example() {
+        assert_eq!(Scratchcards.part_1(EXAMPLE).unwrap(), 13_u32.into());
+        assert_eq!(Scratchcards.part_2(EXAMPLE).unwrap(), 30_u32.into());
+    }
+
+    fn cards() -> impl Strategy<Value = Card> {
+        (1..1000_u32, any::<u128>(), any::<u128>()).prop_map(|(id, numbers, winning_numbers)| {
+            Card {
+                id,
+                numbers: NumberSet(numbers),
+                winning_numbers: NumberSet(winning_numbers),
+            }
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn writes_cards_which_read_back(card in cards()) {
+            properties::round_trips(&card)?;
+        }
+
+        #[test]
+        fn matches_numbers_on_both_sides(card in cards()) {
+            prop_assert!(card.matches() <= card.numbers.len().min(card.winning_numbers.len()));
+            prop_assert_eq!(card.matches() == 0, card.points() == Ok(Total::from(0_u32)));
+        }
+
+        #[test]
+        fn keeps_matches_when_anonymizing(
+            mut rng in properties::rngs(),
+            size in 1..50_usize,
+        ) {
+            let input = Scratchcards.generate(&mut rng, size).unwrap();
+            let anonymized = Scratchcards.anonymize(&input, &mut rng).unwrap().unwrap();
+            let matches = |input: &str| -> Vec<u32> {
+                (parse::lines::<Card>(input).unwrap().iter())
+                    .map(Card::matches)
+                    .collect()
+            };
+            prop_assert_eq!(matches(&anonymized), matches(&input));
+        }
+
+        #[test]
+        fn agrees_with_the_copy_table_on_generated_cards(
+            mut rng in properties::rngs(),
+            size in 1..100_usize,
+        ) {
+            let input = Scratchcards.generate(&mut rng, size).unwrap();
+            properties::agrees(&Scratchcards, Part::Two, &input)?;
+        }
     }
 }
@@ -1,6 +1,11 @@
-use regex::Regex;
+use std::{fmt::Display, str::FromStr};
 
-use crate::utils::prelude::*;
+use crate::utils::{
+    anonymize,
+    interval::{Interval, Piece, PiecewiseMap},
+    minimize::{self, Reducer, Split},
+    prelude::*,
+};
 
 pub struct IfYouGiveASeedAFertilizer;
 
@@ -9,153 +14,1123 @@ impl Problem for IfYouGiveASeedAFertilizer {
         "Day 5: If You Give A Seed A Fertilizer"
     }
 
-    fn part_1(&self, input: &str) {
-        let seeds = load_seeds(input);
-        let map_sets = load_map_sets(input);
-
-        let location = seeds
-            .iter()
-            .map(|seed| map_sets.iter().fold(*seed, |x, map_set| map_set.convert(x)))
-            .min()
-            .unwrap();
+    fn day(&self) -> u32 {
+        5
+    }
 
-        println!("Location: {}", location);
+    fn part_1(&self, input: &str) -> Result<Answer, SolveError> {
+        lowest_location(input, &mut Silent)
     }
 
-    fn part_2(&self, input: &str) {
-        let seed_pairs = load_seed_pairs(input);
-        let map_sets = load_map_sets(input);
+    fn part_2(&self, input: &str) -> Result<Answer, SolveError> {
+        let almanac = load_almanac(input)?;
+        let to_location = almanac.converter(Category::Seed, Category::Location)?;
 
-        let mut location: Seed = Seed::MAX;
+        let location = (to_location.map_intervals(&almanac.seed_ranges()?).first())
+            .map(|range| range.start)
+            .ok_or_else(|| SolveError::Invalid("there are no seeds".to_string()))?;
 
-        for (start, len) in seed_pairs {
-            println!("Seed pair: {}, {}", start, len);
-            'kek: for seed in start..(start + len) {
-                let value = map_sets.iter().fold(seed, |x, map_set| map_set.convert(x));
-                if value < location {
-                    println!("New min location: {}", value);
-                    location = value;
-                    break 'kek;
-                }
-            }
+        Ok(location.into())
+    }
+
+    fn implementations(&self, part: Part) -> Vec<Implementation<'_>> {
+        let main = match part {
+            Part::One => Implementation::new("composed maps", |input| self.part_1(input)),
+            Part::Two => Implementation::new("ranges", |input| self.part_2(input)),
+        };
+        vec![
+            main,
+            Implementation::new("seed loop", move |input| seed_loop(part, input)),
+        ]
+    }
+
+    fn explain(
+        &self,
+        part: Part,
+        input: &str,
+        explainer: &mut dyn Explainer,
+    ) -> Option<Result<Answer, SolveError>> {
+        match part {
+            Part::One => Some(lowest_location(input, explainer)),
+            Part::Two => None,
         }
+    }
+
+    /// Finds what values or ranges of one category become in another, such as
+    /// `humidity 40..50, 60 -> seed`.
+    fn query(&self, input: &str, query: &str) -> Option<Result<Vec<String>, SolveError>> {
+        Some(
+            (query.parse().map_err(SolveError::Query))
+                .and_then(|query| answer_query(&load_almanac(input)?, &query)),
+        )
+    }
+
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Some(generate_almanac(rng, size))
+    }
 
-        println!("\nLocation: {}", location);
+    fn anonymize(&self, input: &str, rng: &mut Rng) -> Option<Result<String, SolveError>> {
+        Some(Ok(anonymize_almanac(rng, input)))
+    }
+
+    fn reducer(&self) -> &dyn Reducer {
+        self
+    }
+
+    fn render_svg(&self, input: &str) -> Option<Result<String, SolveError>> {
+        Some(load_almanac(input).and_then(|almanac| render_svg(&almanac)))
     }
 }
 
 type Seed = u64;
 
-const MATCH_SEEDS: &'static str = r"seeds:((?:\s\d+)+)";
-const MATCH_U32: &'static str = r"\d+";
+const MATCH_SEEDS: &str = r"seeds:((?:\s+\d+)+)";
 
-fn load_seeds(input: &str) -> Vec<Seed> {
-    (Regex::new(MATCH_SEEDS).ok())
-        .and_then(|re| re.captures(input))
-        .and_then(|cap| cap.get(1))
-        .zip(Regex::new(MATCH_U32).ok())
-        .and_then(|(cap, re)| {
-            Some(
-                re.find_iter(cap.as_str())
-                    .filter_map(|l| l.as_str().parse().ok())
-                    .collect(),
-            )
+/// Finds the lowest location of any seed, explaining the path each seed takes to get there.
+fn lowest_location(input: &str, explainer: &mut dyn Explainer) -> Result<Answer, SolveError> {
+    let almanac = load_almanac(input)?;
+    let to_location = almanac.converter(Category::Seed, Category::Location)?;
+    let path = almanac.path(Category::Seed, Category::Location);
+
+    let mut lowest = None;
+    for &seed in &almanac.seeds {
+        let location = to_location.map(seed);
+        explainer.explain(&mut || match &path {
+            Some(path) => (path.iter())
+                .scan(seed, |value, map_set| {
+                    *value = map_set.map.map(*value);
+                    Some(format!("{} {}", map_set.to, value))
+                })
+                .fold(format!("Seed {}", seed), |trace, step| {
+                    format!("{} -> {}", trace, step)
+                }),
+            None => format!("Seed {} ends up at location {}", seed, location),
+        });
+        lowest = Some(lowest.map_or(location, |lowest: Seed| lowest.min(location)));
+    }
+
+    let location = lowest.ok_or_else(|| SolveError::Invalid("there are no seeds".to_string()))?;
+    Ok(location.into())
+}
+
+/// Describes what each value or range in the query becomes, working backwards through the maps
+/// when the query goes against them.
+fn answer_query(almanac: &Almanac, query: &Query) -> Result<Vec<String>, SolveError> {
+    let (from, to) = (query.from, query.to);
+    (query.values.iter())
+        .map(|value| match *value {
+            QueryValue::Single(value) => {
+                let converted = almanac.convert(from, to, value)?;
+                Ok(format!("{} {} is {} {}", from, value, to, converted))
+            }
+            QueryValue::Range(range) => match almanac.path(from, to) {
+                Some(_) => {
+                    let ranges = almanac.converter(from, to)?.map_intervals(&[range]);
+                    Ok(format!(
+                        "{} {} ends up in {} {}",
+                        from,
+                        show_ranges(&[range]),
+                        to,
+                        show_ranges(&ranges)
+                    ))
+                }
+                None => {
+                    let ranges = almanac.preimage(to, from, &[range])?;
+                    Ok(format!(
+                        "{} {} comes from {} {}",
+                        from,
+                        show_ranges(&[range]),
+                        to,
+                        show_ranges(&ranges)
+                    ))
+                }
+            },
         })
-        .unwrap_or(vec![])
+        .collect()
+}
+
+fn show_ranges(ranges: &[Interval<Seed>]) -> String {
+    match ranges.is_empty() {
+        true => "nothing".to_string(),
+        false => (ranges.iter())
+            .map(|range| format!("{}..{}", range.start, range.end))
+            .collect::<Vec<_>>()
+            .join(", "),
+    }
 }
 
-const MATCH_U32_PAIR: &'static str = r"(\d+) (\d+)";
+/// The most seeds [`seed_loop`] will try.
+const SEED_LOOP_LIMIT: Seed = 10_000_000;
+
+/// Finds the lowest location by sending every seed through each map in turn, rather than
+/// combining the maps and mapping whole ranges of seeds at once.
+fn seed_loop(part: Part, input: &str) -> Result<Answer, SolveError> {
+    let almanac = load_almanac(input)?;
+    let path = (almanac.path(Category::Seed, Category::Location))
+        .ok_or_else(|| SolveError::Invalid("no maps lead from seed to location".to_string()))?;
+
+    let seeds: Vec<Interval<Seed>> = match part {
+        Part::One => (almanac.seeds.iter())
+            .map(|&seed| Interval::new(seed, seed.saturating_add(1)))
+            .collect(),
+        Part::Two => almanac.seed_ranges()?,
+    };
+    let count = (seeds.iter()).fold(0, |count: Seed, range| count.saturating_add(range.len()));
+    if count > SEED_LOOP_LIMIT {
+        return Err(SolveError::Unsupported(format!(
+            "{} seeds are too many to try one at a time",
+            count
+        )));
+    }
+
+    let location = (seeds.iter())
+        .flat_map(|range| range.start..range.end)
+        .map(|seed| (path.iter()).fold(seed, |value, map_set| map_set.map.map(value)))
+        .min()
+        .ok_or_else(|| SolveError::Invalid("there are no seeds".to_string()))?;
+    Ok(location.into())
+}
 
-fn load_seed_pairs(input: &str) -> Vec<(Seed, Seed)> {
-    let input = (Regex::new(MATCH_SEEDS).ok())
-        .and_then(|re| re.captures(input))
+fn load_almanac(input: &str) -> Result<Almanac, SolveError> {
+    let seeds = (regex!(MATCH_SEEDS).captures(input))
         .and_then(|cap| cap.get(1))
-        .map(|cap| cap.as_str())
-        .unwrap();
-
-    (Regex::new(MATCH_U32_PAIR).ok())
-        .map(|re| {
-            re.captures_iter(input)
-                .filter_map(|cap| {
-                    (cap.get(1).and_then(|x| x.as_str().parse::<Seed>().ok()))
-                        .zip(cap.get(2).and_then(|x| x.as_str().parse::<Seed>().ok()))
-                })
-                .collect()
+        .map(|cap| parse::ints(cap.as_str()))
+        .transpose()?
+        .unwrap_or_default();
+
+    let map_sets = (parse::sections(input).into_iter())
+        .filter(|section| !section.starts_with("seeds:"))
+        .map(str::parse)
+        .collect::<Result<_, _>>()?;
+
+    Ok(Almanac { seeds, map_sets })
+}
+
+/// The values the generated almanacs use, which are about as large as in real almanacs.
+const GENERATED_SEEDS: Seed = 1 << 32;
+
+/// Generates an almanac with `size` seed ranges and `size` maps between each category.
+///
+/// Each map set cuts the values into pieces and lays them back down in a different order, so
+/// like in real almanacs, it's a bijection on the values it covers.
+fn generate_almanac(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1);
+    let seeds: Vec<String> = (0..size)
+        .map(|_| {
+            let len = rng.range(1..=GENERATED_SEEDS / 16);
+            format!("{} {}", rng.range(0..=GENERATED_SEEDS - len), len)
+        })
+        .collect();
+
+    let mut almanac = format!("seeds: {}\n", seeds.join(" "));
+    for pair in Category::ALL.windows(2) {
+        let mut cuts: Vec<Seed> = (0..size)
+            .map(|_| rng.range(1..=GENERATED_SEEDS - 1))
+            .chain([0, GENERATED_SEEDS])
+            .collect();
+        cuts.sort();
+        cuts.dedup();
+
+        let mut pieces: Vec<Interval<Seed>> = (cuts.windows(2))
+            .map(|w| Interval::new(w[0], w[1]))
+            .collect();
+        rng.shuffle(&mut pieces);
+
+        let mut dest = 0;
+        let mut maps = vec![];
+        for piece in pieces {
+            maps.push(format!("{} {} {}", dest, piece.start, piece.len()));
+            dest += piece.len();
+        }
+        rng.shuffle(&mut maps);
+
+        almanac += &format!("\n{}-to-{} map:\n{}\n", pair[0], pair[1], maps.join("\n"));
+    }
+    almanac
+}
+
+/// Rewrites the almanac with new seeds and new maps, keeping the order of each map set's ranges.
+///
+/// Ranges keep their place among the others on both sides of the map, and ranges which touch
+/// still touch, so ranges which didn't overlap still don't. Map sets which already overlap or
+/// can't be read are just renumbered.
+fn anonymize_almanac(rng: &mut Rng, input: &str) -> String {
+    let sections: Vec<String> = (parse::sections(input).into_iter())
+        .map(|section| {
+            let Some((header, rows)) = section.split_once('\n') else {
+                return anonymize::renumber(rng, section);
+            };
+            let maps: Option<Vec<Map>> = (rows.lines())
+                .map(|line| line.parse().ok())
+                .collect::<Option<_>>()
+                .and_then(|maps| shuffle_maps(rng, maps));
+            match maps {
+                Some(maps) if !section.starts_with("seeds:") => {
+                    let rows: Vec<String> = (maps.iter())
+                        .map(|m| format!("{} {} {}", m.dest, m.src, m.len))
+                        .collect();
+                    format!("{}\n{}", header, rows.join("\n"))
+                }
+                _ => anonymize::renumber(rng, section),
+            }
         })
-        .unwrap_or(vec![])
+        .collect();
+    sections.join("\n\n") + "\n"
 }
 
-const MATCH_MAP_SET: &'static str = r"[a-z\-]+ map:\n((?:\d+ \d+ \d+\n)+)";
+/// Gives each map a new length and moves both of its sides, or returns `None` if the maps overlap
+/// or the new ones wouldn't fit in a [`Seed`].
+fn shuffle_maps(rng: &mut Rng, maps: Vec<Map>) -> Option<Vec<Map>> {
+    let lens: Vec<Seed> = (maps.iter())
+        .map(|m| anonymize::number_like(rng, &m.len.to_string()).parse().ok())
+        .collect::<Option<_>>()?;
+    let sources: Vec<(Seed, Seed)> = maps.iter().map(|m| (m.src, m.len)).collect();
+    let dests: Vec<(Seed, Seed)> = maps.iter().map(|m| (m.dest, m.len)).collect();
 
-fn load_map_sets(input: &str) -> Vec<MapSet> {
-    if let Some(re) = Regex::new(MATCH_MAP_SET).ok() {
-        re.captures_iter(input)
-            .map(|cap| cap.get(1).unwrap().as_str())
-            .map(MapSet::from)
-            .collect()
-    } else {
-        vec![]
+    let sources = respace(rng, &sources, &lens)?;
+    let dests = respace(rng, &dests, &lens)?;
+    Some(
+        (0..maps.len())
+            .map(|i| Map {
+                dest: dests[i],
+                src: sources[i],
+                len: lens[i],
+            })
+            .collect(),
+    )
+}
+
+/// Moves ranges given as starts and lengths so that they have the new lengths, keeping them in the
+/// same order but with new gaps between them. Ranges which touched still touch.
+fn respace(rng: &mut Rng, ranges: &[(Seed, Seed)], lens: &[Seed]) -> Option<Vec<Seed>> {
+    let mut order: Vec<usize> = (0..ranges.len()).collect();
+    order.sort_by_key(|&i| ranges[i].0);
+
+    let mut starts = vec![0; ranges.len()];
+    let (mut end, mut new_end): (Seed, Seed) = (0, 0);
+    for i in order {
+        let (start, len) = ranges[i];
+        let gap = start.checked_sub(end)?;
+        let gap: Seed = anonymize::number_like(rng, &gap.to_string()).parse().ok()?;
+
+        starts[i] = new_end.checked_add(gap)?;
+        end = start.checked_add(len)?;
+        new_end = starts[i].checked_add(lens[i])?;
     }
+    Some(starts)
 }
 
-struct MapSet {
-    maps: Vec<Map>,
+/// Removes whole map sets, then single maps, then seeds two at a time so that they still pair up
+/// into ranges.
+impl Reducer for IfYouGiveASeedAFertilizer {
+    fn split<'a>(&self, input: &'a str, level: usize) -> Option<Split<'a>> {
+        let Some(almanac) = AlmanacText::read(input) else {
+            return minimize::Lines.split(input, level);
+        };
+        match level {
+            0 => Some(Split::new(almanac.map_sets.len(), move |keep| {
+                AlmanacText {
+                    seeds: almanac.seeds.clone(),
+                    map_sets: minimize::kept(&almanac.map_sets, keep).cloned().collect(),
+                }
+                .write()
+            })),
+            1 => {
+                let count = almanac.map_sets.iter().map(|(_, maps)| maps.len()).sum();
+                Some(Split::new(count, move |keep| {
+                    let mut keep = keep.iter();
+                    let map_sets = (almanac.map_sets.iter())
+                        .map(|(header, maps)| {
+                            let maps = (maps.iter())
+                                .filter(|_| *keep.next().unwrap_or(&true))
+                                .copied()
+                                .collect();
+                            (*header, maps)
+                        })
+                        .collect();
+                    AlmanacText {
+                        seeds: almanac.seeds.clone(),
+                        map_sets,
+                    }
+                    .write()
+                }))
+            }
+            2 => Some(Split::new(almanac.seeds.len().div_ceil(2), move |keep| {
+                let seeds = (almanac.seeds.iter().enumerate())
+                    .filter(|(i, _)| keep[i / 2])
+                    .map(|(_, seed)| *seed)
+                    .collect();
+                AlmanacText {
+                    seeds,
+                    map_sets: almanac.map_sets.clone(),
+                }
+                .write()
+            })),
+            _ => None,
+        }
+    }
+}
+
+/// The text of an almanac's seeds and of the maps in each map set, which can be written back out
+/// without some of them.
+struct AlmanacText<'a> {
+    seeds: Vec<&'a str>,
+    map_sets: Vec<(&'a str, Vec<&'a str>)>,
+}
+
+impl<'a> AlmanacText<'a> {
+    /// Reads the almanac's text, or returns `None` if it has no seeds section.
+    pub fn read(input: &'a str) -> Option<Self> {
+        let sections = parse::sections(input);
+        let seeds = (sections.iter())
+            .find_map(|section| section.strip_prefix("seeds:"))?
+            .split_whitespace()
+            .collect();
+        let map_sets = (sections.iter())
+            .filter(|section| !section.starts_with("seeds:"))
+            .map(|section| {
+                let mut lines = section.lines();
+                let header = lines.next().unwrap_or_default();
+                (header, lines.filter(|l| !l.trim().is_empty()).collect())
+            })
+            .collect();
+        Some(Self { seeds, map_sets })
+    }
+
+    pub fn write(&self) -> String {
+        let mut text = format!("seeds: {}\n", self.seeds.join(" "));
+        for (header, maps) in &self.map_sets {
+            text += &format!("\n{}\n", header);
+            for map in maps {
+                text += &format!("{}\n", map);
+            }
+        }
+        text
+    }
+}
+
+/// A kind of thing which the almanac maps between.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Category {
+    Seed,
+    Soil,
+    Fertilizer,
+    Water,
+    Light,
+    Temperature,
+    Humidity,
+    Location,
+}
+
+impl Category {
+    /// Every category, in the order the almanac maps between them.
+    pub const ALL: [Category; 8] = [
+        Category::Seed,
+        Category::Soil,
+        Category::Fertilizer,
+        Category::Water,
+        Category::Light,
+        Category::Temperature,
+        Category::Humidity,
+        Category::Location,
+    ];
 }
 
-impl MapSet {
-    pub fn convert(&self, value: Seed) -> Seed {
-        if let Some(map) = self.maps.iter().find(|m| m.is_match(value)) {
-            map.convert(value)
-        } else {
-            value
+impl FromStr for Category {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "seed" => Ok(Category::Seed),
+            "soil" => Ok(Category::Soil),
+            "fertilizer" => Ok(Category::Fertilizer),
+            "water" => Ok(Category::Water),
+            "light" => Ok(Category::Light),
+            "temperature" => Ok(Category::Temperature),
+            "humidity" => Ok(Category::Humidity),
+            "location" => Ok(Category::Location),
+            _ => Err(format!("{:?} is not a known category", value)),
         }
     }
 }
 
-impl From<&str> for MapSet {
-    fn from(value: &str) -> Self {
-        Self {
-            maps: value
-                .split('\n')
-                .filter(|l| l.len() != 0)
-                .map(Map::from)
-                .collect(),
+impl Display for Category {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", format!("{:?}", self).to_lowercase())
+    }
+}
+
+/// Values or ranges of one category to find in another, such as `humidity 40..50, 60 -> seed`.
+struct Query {
+    from: Category,
+    to: Category,
+    values: Vec<QueryValue>,
+}
+
+enum QueryValue {
+    Single(Seed),
+    Range(Interval<Seed>),
+}
+
+impl FromStr for Query {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let shape = || {
+            format!(
+                "{:?} should look like `<category> <values> -> <category>`",
+                value
+            )
+        };
+        let (values, to) = value.split_once("->").ok_or_else(shape)?;
+        let (from, values) = (values.trim().split_once(char::is_whitespace)).ok_or_else(shape)?;
+
+        let values = (values.split(','))
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(|v| {
+                let number = |n: &str| {
+                    (n.trim().parse::<Seed>())
+                        .map_err(|err| format!("{:?} is not a valid value: {}", v, err))
+                };
+                match v.split_once("..") {
+                    Some((start, end)) => match (number(start)?, number(end)?) {
+                        (start, end) if start < end => {
+                            Ok(QueryValue::Range(Interval::new(start, end)))
+                        }
+                        _ => Err(format!("{:?} is an empty range", v)),
+                    },
+                    None => Ok(QueryValue::Single(number(v)?)),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            from: from.trim().parse()?,
+            to: to.trim().parse()?,
+            values,
+        })
+    }
+}
+
+/// The seeds to plant and the maps between each category, which form a graph.
+struct Almanac {
+    seeds: Vec<Seed>,
+    map_sets: Vec<MapSet>,
+}
+
+impl Almanac {
+    /// Interprets the seeds as pairs of starts and lengths.
+    pub fn seed_ranges(&self) -> Result<Vec<Interval<Seed>>, SolveError> {
+        (self.seeds.chunks_exact(2))
+            .map(|pair| match pair[0].checked_add(pair[1]) {
+                Some(end) => Ok(Interval::new(pair[0], end)),
+                None => Err(SolveError::Overflow("reading the seed ranges")),
+            })
+            .collect()
+    }
+
+    /// Gets a single map which converts values of one category into another.
+    ///
+    /// If the maps only lead the other way, their combined map is inverted instead.
+    pub fn converter(
+        &self,
+        from: Category,
+        to: Category,
+    ) -> Result<PiecewiseMap<Seed>, SolveError> {
+        if let Some(path) = self.path(from, to) {
+            return Ok(compose(&path));
+        }
+
+        match self.path(to, from) {
+            Some(path) => compose(&path).invert().ok_or_else(|| {
+                SolveError::Invalid(format!("the {} to {} maps can't be inverted", to, from))
+            }),
+            None => Err(SolveError::Invalid(format!(
+                "no maps lead from {} to {}",
+                from, to
+            ))),
+        }
+    }
+
+    /// Converts a single value of one category into another.
+    pub fn convert(&self, from: Category, to: Category, value: Seed) -> Result<Seed, SolveError> {
+        Ok(self.converter(from, to)?.map(value))
+    }
+
+    /// Finds which values of one category end up in the given ranges of a later category.
+    pub fn preimage(
+        &self,
+        from: Category,
+        to: Category,
+        ranges: &[Interval<Seed>],
+    ) -> Result<Vec<Interval<Seed>>, SolveError> {
+        let path = self
+            .path(from, to)
+            .ok_or_else(|| SolveError::Invalid(format!("no maps lead from {} to {}", from, to)))?;
+        Ok(compose(&path).preimage(ranges))
+    }
+
+    /// Finds the shortest chain of map sets leading from one category to another.
+    fn path(&self, from: Category, to: Category) -> Option<Vec<&MapSet>> {
+        let mut paths: Vec<(Category, Vec<&MapSet>)> = vec![(from, vec![])];
+        let mut visited = vec![from];
+
+        while !paths.is_empty() {
+            let mut next = vec![];
+            for (category, path) in paths {
+                if category == to {
+                    return Some(path);
+                }
+                for map_set in self.map_sets.iter().filter(|m| m.from == category) {
+                    if !visited.contains(&map_set.to) {
+                        visited.push(map_set.to);
+                        let mut path = path.clone();
+                        path.push(map_set);
+                        next.push((map_set.to, path));
+                    }
+                }
+            }
+            paths = next;
         }
+
+        None
+    }
+}
+
+fn compose(path: &[&MapSet]) -> PiecewiseMap<Seed> {
+    (path.iter()).fold(PiecewiseMap::new(vec![]), |map, map_set| {
+        map.then(&map_set.map)
+    })
+}
+
+/// The maps converting one category into another.
+struct MapSet {
+    from: Category,
+    to: Category,
+    map: PiecewiseMap<Seed>,
+}
+
+impl FromStr for MapSet {
+    type Err = ParseError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (key, rows) = parse::header(value)
+            .ok_or_else(|| ParseError::mismatch("MapSet", "<from>-to-<to> map:", value))?;
+        let Header { from, to } = key.parse()?;
+        let maps: Vec<Map> = rows
+            .lines()
+            .filter(|l| !l.is_empty())
+            .map(|line| line.parse().and_then(Map::checked))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            from,
+            to,
+            map: PiecewiseMap::new(maps.iter().map(Map::piece).collect()),
+        })
     }
 }
 
+#[derive(FromInput)]
+#[pattern(r"(?P<from>[a-z]+)-to-(?P<to>[a-z]+) map")]
+struct Header {
+    from: Category,
+    to: Category,
+}
+
+#[derive(Debug, PartialEq, FromInput)]
+#[pattern(r"(?P<dest>\d+)\s+(?P<src>\d+)\s+(?P<len>\d+)")]
 struct Map {
     dest: Seed,
     src: Seed,
     len: Seed,
 }
 
+impl Display for Map {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {}", self.dest, self.src, self.len)
+    }
+}
+
 impl Map {
-    pub fn is_match(&self, value: Seed) -> bool {
-        self.src <= value && value <= self.src + self.len
+    /// Rejects maps whose ranges run past the largest value a [`Seed`] can hold.
+    fn checked(self) -> Result<Self, ParseError> {
+        if self.src.checked_add(self.len).is_some() && self.dest.checked_add(self.len).is_some() {
+            return Ok(self);
+        }
+        Err(ParseError::InvalidField {
+            target: "Map",
+            field: "len",
+            value: self.len.to_string(),
+            reason: "the range runs past the largest seed".to_string(),
+            line: format!("{} {} {}", self.dest, self.src, self.len),
+        })
+    }
+
+    /// Gets the half-open range of values this map applies to.
+    pub fn source(&self) -> Interval<Seed> {
+        Interval::from_len(self.src, self.len)
+    }
+
+    pub fn piece(&self) -> Piece<Seed> {
+        Piece {
+            source: self.source(),
+            dest: self.dest,
+        }
+    }
+}
+
+const MARGIN: f64 = 80.0;
+const COLUMN_GAP: f64 = 200.0;
+const AXIS_HEIGHT: f64 = 600.0;
+const LEGEND_LINE: f64 = 20.0;
+const PALETTE: [&str; 8] = [
+    "#4e79a7", "#f28e2b", "#e15759", "#76b7b2", "#59a14f", "#edc948", "#b07aa1", "#ff9da7",
+];
+
+/// Draws the maps from seeds to locations as an SVG.
+///
+/// Each category is a vertical axis, with values increasing downwards. Each map is a band from its
+/// source range on one axis to its destination range on the next, and the ranges each seed range
+/// ends up in are marked on every axis in that seed range's color.
+fn render_svg(almanac: &Almanac) -> Result<String, SolveError> {
+    let path = (almanac.path(Category::Seed, Category::Location))
+        .ok_or_else(|| SolveError::Invalid("no maps lead from seed to location".to_string()))?;
+
+    let mut traces: Vec<Vec<Vec<Interval<Seed>>>> = (almanac.seed_ranges()?.into_iter())
+        .map(|range| vec![vec![range]])
+        .collect();
+    for map_set in &path {
+        for trace in &mut traces {
+            let next = map_set.map.map_intervals(&trace[trace.len() - 1]);
+            trace.push(next);
+        }
+    }
+
+    let ends = (path.iter())
+        .flat_map(|m| m.map.pieces())
+        .flat_map(|piece| [piece.source.end, piece.image().end])
+        .chain(traces.iter().flatten().flatten().map(|range| range.end));
+    let max = ends.max().unwrap_or(0).max(1);
+
+    let x = |column: usize| MARGIN + column as f64 * COLUMN_GAP;
+    let y = |value: Seed| MARGIN + value as f64 / max as f64 * AXIS_HEIGHT;
+    let width = 2.0 * MARGIN + path.len() as f64 * COLUMN_GAP;
+    let height = 2.0 * MARGIN + AXIS_HEIGHT + traces.len() as f64 * LEGEND_LINE;
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" \
+         viewBox=\"0 0 {0} {1}\" font-family=\"sans-serif\" font-size=\"14\">\n\
+         <rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n",
+        width, height
+    );
+
+    for (column, map_set) in path.iter().enumerate() {
+        for piece in map_set.map.pieces() {
+            let (source, dest) = (piece.source, piece.image());
+            svg.push_str(&format!(
+                "<polygon points=\"{},{} {},{} {},{} {},{}\" fill=\"#888\" fill-opacity=\"0.3\" \
+                 stroke=\"#666\" stroke-width=\"0.5\"><title>{} {}..{} to {} {}..{}</title></polygon>\n",
+                x(column),
+                y(source.start),
+                x(column + 1),
+                y(dest.start),
+                x(column + 1),
+                y(dest.end),
+                x(column),
+                y(source.end),
+                map_set.from,
+                source.start,
+                source.end,
+                map_set.to,
+                dest.start,
+                dest.end,
+            ));
+        }
+    }
+
+    let categories: Vec<Category> = (std::iter::once(Category::Seed))
+        .chain(path.iter().map(|m| m.to))
+        .collect();
+    for (column, category) in categories.iter().enumerate() {
+        svg.push_str(&format!(
+            "<line x1=\"{0}\" y1=\"{1}\" x2=\"{0}\" y2=\"{2}\" stroke=\"black\"/>\n\
+             <text x=\"{0}\" y=\"{3}\" text-anchor=\"middle\">{4}</text>\n",
+            x(column),
+            y(0),
+            y(max),
+            MARGIN / 2.0,
+            category
+        ));
     }
+    svg.push_str(&format!(
+        "<text x=\"{0}\" y=\"{1}\" text-anchor=\"end\">0</text>\n\
+         <text x=\"{0}\" y=\"{2}\" text-anchor=\"end\">{3}</text>\n",
+        x(0) - 10.0,
+        y(0) + 5.0,
+        y(max) + 5.0,
+        max
+    ));
 
-    pub fn convert(&self, value: Seed) -> Seed {
-        if self.src < self.dest {
-            value + (self.dest - self.src)
-        } else {
-            value - (self.src - self.dest)
+    for (i, trace) in traces.iter().enumerate() {
+        let color = PALETTE[i % PALETTE.len()];
+        for (column, ranges) in trace.iter().enumerate() {
+            for range in ranges {
+                svg.push_str(&format!(
+                    "<rect x=\"{}\" y=\"{}\" width=\"8\" height=\"{}\" fill=\"{}\">\
+                     <title>{} {}..{}</title></rect>\n",
+                    x(column) - 4.0,
+                    y(range.start),
+                    (y(range.end) - y(range.start)).max(1.0),
+                    color,
+                    categories[column],
+                    range.start,
+                    range.end,
+                ));
+            }
         }
+
+        let seeds = trace[0][0];
+        svg.push_str(&format!(
+            "<text x=\"{}\" y=\"{}\" fill=\"{}\">seeds {}..{}</text>\n",
+            x(0),
+            2.0 * MARGIN + AXIS_HEIGHT + i as f64 * LEGEND_LINE - MARGIN / 2.0,
+            color,
+            seeds.start,
+            seeds.end
+        ));
     }
+
+    svg.push_str("</svg>\n");
+    Ok(svg)
 }
 
-const MATCH_MAP: &'static str = r"(\d+) (\d+) (\d+)";
+#[cfg(test)]
+mod tests {
+    use proptest::{
+        prelude::{any, Strategy},
+        prop_assert, prop_assert_eq, proptest,
+    };
+
+    use crate::utils::{explain::Trace, properties};
+
+    use super::*;
+
+    const EXAMPLE: &str = "seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15
+
+fertilizer-to-water map:
+49 53 8
+0 11 42
+42 0 7
+57 7 4
+
+water-to-light map:
+88 18 7
+18 25 70
+
+light-to-temperature map:
+45 77 23
+81 45 19
+68 64 13
+
+temperature-to-humidity map:
+0 69 1
+1 0 69
+
+humidity-to-location map:
+60 56 37
+56 93 4
+";
+
+    #[test]
+    fn converts_values_at_map_boundaries() {
+        let map_set: MapSet = "seed-to-soil map:\n50 98 2".parse().unwrap();
+        assert_eq!(map_set.map.map(97), 97);
+        assert_eq!(map_set.map.map(98), 50);
+        assert_eq!(map_set.map.map(99), 51);
+        assert_eq!(map_set.map.map(100), 100);
+    }
+
+    #[test]
+    fn converts_seed_ranges() {
+        let map_set: MapSet = "seed-to-soil map:\n50 98 2\n52 50 48".parse().unwrap();
+        assert_eq!(
+            map_set
+                .map
+                .map_intervals(&[Interval::from_len(79, 14), Interval::from_len(55, 13)]),
+            vec![Interval::new(57, 70), Interval::new(81, 95)]
+        );
+    }
+
+    #[test]
+    fn rejects_ranges_which_overflow() {
+        let map_set = format!("seed-to-soil map:\n0 {} 2", Seed::MAX);
+        assert!(map_set.parse::<MapSet>().is_err());
+
+        let almanac = format!("seeds: {} 2\n", Seed::MAX);
+        assert_eq!(
+            load_almanac(&almanac).unwrap().seed_ranges(),
+            Err(SolveError::Overflow("reading the seed ranges"))
+        );
+
+        let almanac = "seeds: 99999999999999999999 5 1 2\n\nseed-to-location map:\n0 1 10\n";
+        let problem = IfYouGiveASeedAFertilizer;
+        for part in Part::ALL {
+            assert_eq!(
+                problem.solve(part, almanac),
+                Err(SolveError::Overflow("reading a number"))
+            );
+        }
+    }
+
+    #[test]
+    fn parses_map_set_categories() {
+        let map_set: MapSet = "light-to-temperature map:\n45 77 23".parse().unwrap();
+        assert_eq!(map_set.from, Category::Light);
+        assert_eq!(map_set.to, Category::Temperature);
+        assert!("light-to-dark map:\n45 77 23".parse::<MapSet>().is_err());
+    }
+
+    #[test]
+    fn converts_between_any_categories() {
+        let almanac = load_almanac(EXAMPLE).unwrap();
+        assert_eq!(almanac.convert(Category::Seed, Category::Soil, 79), Ok(81));
+        assert_eq!(
+            almanac.convert(Category::Seed, Category::Location, 79),
+            Ok(82)
+        );
+        assert_eq!(almanac.convert(Category::Soil, Category::Water, 81), Ok(81));
+        assert_eq!(
+            almanac.convert(Category::Location, Category::Seed, 82),
+            Ok(79)
+        );
+        assert_eq!(
+            almanac.convert(Category::Location, Category::Soil, 82),
+            Ok(81)
+        );
+    }
+
+    #[test]
+    fn finds_seeds_ending_up_in_ranges() {
+        let almanac = load_almanac(EXAMPLE).unwrap();
+        let seeds = almanac
+            .preimage(Category::Seed, Category::Location, &[Interval::new(46, 47)])
+            .unwrap();
+        assert!(seeds.iter().any(|range| range.contains(82)));
+        for range in seeds {
+            for seed in range.start..range.end {
+                assert_eq!(
+                    almanac.convert(Category::Seed, Category::Location, seed),
+                    Ok(46)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn renders_the_almanac_as_svg() {
+        let svg = render_svg(&load_almanac(EXAMPLE).unwrap()).unwrap();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert!(svg.contains(">seed</text>") && svg.contains(">location</text>"));
+        assert_eq!(svg.matches("<polygon").count(), 18);
+        assert!(svg.contains("<title>location 46..56</title>"));
+
+        let unreachable = "seeds: 1 2\n\nseed-to-soil map:\n1 2 3\n";
+        assert!(render_svg(&load_almanac(unreachable).unwrap()).is_err());
+    }
+
+    #[test]
+    fn explains_the_path_of_each_seed() {
+        let mut trace = Trace::new(vec![]);
+        let answer = IfYouGiveASeedAFertilizer.explain(Part::One, EXAMPLE, &mut trace);
+        assert_eq!(answer.unwrap().unwrap(), 35_u64.into());
+
+        let text = String::from_utf8(trace.finish().unwrap()).unwrap();
+        assert_eq!(text.lines().count(), 4);
+        assert!(text.starts_with(
+            "  Seed 79 -> soil 81 -> fertilizer 81 -> water 81 -> light 74 -> temperature 78 \
+             -> humidity 78 -> location 82\n"
+        ));
+    }
+
+    #[test]
+    fn answers_queries() {
+        assert_eq!(
+            IfYouGiveASeedAFertilizer.query(EXAMPLE, "location 46..56, 82 -> seed"),
+            Some(Ok(vec![
+                "location 46..56 comes from seed 82..92".to_string(),
+                "location 82 is seed 79".to_string(),
+            ]))
+        );
+        assert_eq!(
+            IfYouGiveASeedAFertilizer.query(EXAMPLE, "seed 79..93 -> soil"),
+            Some(Ok(vec!["seed 79..93 ends up in soil 81..95".to_string()]))
+        );
+
+        for query in ["seed 79", "seed 79 -> compost", "seed 9..3 -> soil"] {
+            let answer = IfYouGiveASeedAFertilizer.query(EXAMPLE, query);
+            assert!(matches!(answer, Some(Err(SolveError::Query(_)))));
+        }
+    }
+
+    #[test]
+    fn generates_bijective_almanacs() {
+        let input = IfYouGiveASeedAFertilizer
+            .generate(&mut Rng::new(0), 20)
+            .unwrap();
+        let almanac = load_almanac(&input).unwrap();
+        assert_eq!(almanac.seeds.len(), 40);
+        assert_eq!(almanac.map_sets.len(), 7);
+
+        let everything = [Interval::new(0, GENERATED_SEEDS)];
+        for map_set in &almanac.map_sets {
+            assert_eq!(map_set.map.map_intervals(&everything), everything);
+        }
+        assert!(almanac
+            .converter(Category::Location, Category::Seed)
+            .is_ok());
+        assert!(IfYouGiveASeedAFertilizer.part_1(&input).is_ok());
+        assert!(IfYouGiveASeedAFertilizer.part_2(&input).is_ok());
+    }
 
-impl From<&str> for Map {
-    fn from(value: &str) -> Self {
-        let cap = (Regex::new(MATCH_MAP).ok())
-            .and_then(|re| re.captures(value))
+    #[test]
+    fn anonymizes_almanacs_without_overlapping_ranges() {
+        let anonymized = (IfYouGiveASeedAFertilizer.anonymize(EXAMPLE, &mut Rng::new(0)))
+            .unwrap()
             .unwrap();
+        assert_ne!(anonymized, EXAMPLE);
+        assert_eq!(anonymized.lines().count(), EXAMPLE.lines().count());
+
+        let almanac = load_almanac(&anonymized).unwrap();
+        assert_eq!(almanac.seeds.len(), 4);
+        for map_set in &almanac.map_sets {
+            let pieces = map_set.map.pieces();
+            for (a, b) in pieces.iter().zip(pieces.iter().skip(1)) {
+                assert!(a.source.end <= b.source.start);
+            }
+            let mut images: Vec<Interval<Seed>> = pieces.iter().map(Piece::image).collect();
+            images.sort_by_key(|image| image.start);
+            for (a, b) in images.iter().zip(images.iter().skip(1)) {
+                assert!(a.end <= b.start);
+            }
+        }
+    }
+
+    #[test]
+    fn respaces_ranges_in_the_same_order() {
+        let mut rng = Rng::new(0);
+        let starts = respace(&mut rng, &[(50, 48), (98, 2), (0, 10)], &[5, 1, 3]).unwrap();
+        assert_eq!(starts[2], 0);
+        assert!(starts[2] + 3 <= starts[0]);
+        assert_eq!(starts[0] + 5, starts[1]);
+        assert_eq!(respace(&mut rng, &[(0, 10), (5, 10)], &[1, 1]), None);
+    }
+
+    #[test]
+    fn agrees_with_the_seed_loop() {
+        let problem = IfYouGiveASeedAFertilizer;
+        let mut rng = Rng::new(0);
+        let generated = generate_almanac(&mut rng, 5);
+        for input in [
+            EXAMPLE,
+            "seeds: 5 3 20 2\n\nseed-to-location map:\n0 6 10\n",
+        ] {
+            for part in Part::ALL {
+                let results: Vec<_> = (problem.implementations(part).iter())
+                    .map(|implementation| implementation.solve(input))
+                    .collect();
+                assert_eq!(results.len(), 2);
+                assert_eq!(results[0], results[1]);
+            }
+        }
+
+        // Generated seed ranges are far too long to loop over.
+        assert_eq!(seed_loop(Part::One, &generated), problem.part_1(&generated));
+        assert!(matches!(
+            seed_loop(Part::Two, &generated),
+            Err(SolveError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn minimizes_seeds_in_pairs() {
+        let problem = IfYouGiveASeedAFertilizer;
+        let minimized = minimize::minimize(EXAMPLE, &problem, &mut |input| {
+            problem.part_1(input) == Ok(35_u64.into())
+        });
+        assert!(minimized.starts_with("seeds: 55 13\n"));
+        assert!(minimized.lines().count() < EXAMPLE.lines().count());
+        assert_eq!(problem.part_1(&minimized), Ok(35_u64.into()));
+    }
+
+    #[test]
+    fn solves_the_example() {
+        let problem = IfYouGiveASeedAFertilizer;
+        assert_eq!(problem.part_1(EXAMPLE).unwrap(), 35_u64.into());
+        assert_eq!(problem.part_2(EXAMPLE).unwrap(), 46_u64.into());
+    }
+
+    fn maps() -> impl Strategy<Value = Map> {
+        (any::<Seed>(), any::<Seed>(), any::<Seed>()).prop_map(|(dest, src, len)| Map {
+            dest,
+            src,
+            len,
+        })
+    }
+
+    fn almanacs() -> impl Strategy<Value = String> {
+        (properties::rngs(), 1..10_usize)
+            .prop_map(|(mut rng, size)| generate_almanac(&mut rng, size))
+    }
+
+    proptest! {
+        #[test]
+        fn writes_maps_which_read_back(map in maps()) {
+            properties::round_trips(&map)?;
+        }
+
+        #[test]
+        fn converts_generated_almanacs_one_to_one(
+            input in almanacs(),
+            a in 0..GENERATED_SEEDS,
+            b in 0..GENERATED_SEEDS,
+        ) {
+            let almanac = load_almanac(&input).unwrap();
+            let convert = |from, to, value| almanac.convert(from, to, value).unwrap();
+            let (x, y) = (
+                convert(Category::Seed, Category::Location, a),
+                convert(Category::Seed, Category::Location, b),
+            );
+            prop_assert!(x < GENERATED_SEEDS);
+            prop_assert_eq!(a == b, x == y);
+            prop_assert_eq!(convert(Category::Location, Category::Seed, x), a);
+        }
+
+        #[test]
+        fn agrees_with_the_seed_loop_on_generated_almanacs(input in almanacs()) {
+            properties::agrees(&IfYouGiveASeedAFertilizer, Part::One, &input)?;
+        }
 
-        Self {
-            dest: cap.get(1).unwrap().as_str().parse().unwrap(),
-            src: cap.get(2).unwrap().as_str().parse().unwrap(),
-            len: cap.get(3).unwrap().as_str().parse().unwrap(),
+        #[test]
+        fn anonymizes_generated_almanacs_into_valid_ones(
+            input in almanacs(),
+            mut rng in properties::rngs(),
+        ) {
+            let anonymized = anonymize_almanac(&mut rng, &input);
+            let almanac = load_almanac(&anonymized).unwrap();
+            prop_assert_eq!(almanac.map_sets.len(), 7);
+            prop_assert!(IfYouGiveASeedAFertilizer.part_2(&anonymized).is_ok());
         }
     }
 }
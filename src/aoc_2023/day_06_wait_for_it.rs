@@ -1,6 +1,29 @@
-use regex::Regex;
+//! # Day 6: Wait For It
+//!
+//! ## Part 1
+//!
+//! Each toy boat race lasts a fixed time, and holding the boat's button for `h` milliseconds at
+//! the start makes it travel at `h` millimeters per millisecond for the rest of the race. For
+//! example:
+//!
+//! ```
+//! Time:      7  15   30
+//! Distance:  9  40  200
+//! ```
+//!
+//! Find how many hold times beat each race's record distance, and multiply them together. For
+//! the previous example, the product is 288.
+//!
+//! ## Part 2
+//!
+//! The numbers on each row are actually a single number with bad kerning, so there is only one
+//! race. Find how many hold times beat its record. For the previous example, there are 71503.
 
-use crate::utils::prelude::Problem;
+use crate::utils::{
+    anonymize,
+    minimize::{self, Reducer, Split},
+    prelude::*,
+};
 
 pub struct WaitForIt;
 
@@ -9,64 +32,492 @@ impl Problem for WaitForIt {
         "Day 6: Wait For It"
     }
 
-    fn part_1(&self, input: &str) {
-        let races = load_races(input);
+    fn day(&self) -> u32 {
+        6
     }
 
-    fn part_2(&self, input: &str) {
-        let races = load_races(input);
+    fn part_1(&self, input: &str) -> Result<Answer, SolveError> {
+        let product = (load_races(input)?.iter())
+            .map(|race| Total::from(race.ways_to_win()))
+            .checked_product("multiplying the ways to win")?;
+        Ok(product.into())
+    }
+
+    fn part_2(&self, input: &str) -> Result<Answer, SolveError> {
+        Ok(load_kerned_race(input)?.ways_to_win().into())
+    }
+
+    fn implementations(&self, part: Part) -> Vec<Implementation<'_>> {
+        let counting = move |input: &str| -> Result<Answer, SolveError> {
+            match part {
+                Part::One => (load_races(input)?.iter())
+                    .map(|race| Ok(Total::from(race.count_ways_to_win()?)))
+                    .collect::<Result<Vec<_>, SolveError>>()?
+                    .into_iter()
+                    .checked_product("multiplying the ways to win")
+                    .map(Answer::from),
+                Part::Two => Ok(load_kerned_race(input)?.count_ways_to_win()?.into()),
+            }
+        };
+        vec![
+            Implementation::new("quadratic", move |input| self.solve(part, input)),
+            Implementation::new("counting", counting),
+        ]
+    }
+
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Some(generate_races(rng, size))
+    }
+
+    /// Gives each race a new time with as many digits, and a record just as close to the best
+    /// distance, so the same races can be won.
+    fn anonymize(&self, input: &str, rng: &mut Rng) -> Option<Result<String, SolveError>> {
+        let Ok(races) = load_races(input) else {
+            return Some(Ok(anonymize::renumber(rng, input)));
+        };
+
+        let races: Vec<Race> = (races.into_iter())
+            .map(|race| {
+                let winnable = race.ways_to_win() > 0;
+                let time = (anonymize::number_like(rng, &race.time.to_string()).parse())
+                    .unwrap_or(race.time);
+                // A race lasting a single millisecond can't be won, however low its record is.
+                let time = if winnable { time.max(2) } else { time };
+                // Records of long races times a best distance can be too big even for a u128, so
+                // those are just put as close to the best distance as they can be.
+                let best = best_distance(time);
+                let scaled = (race.distance as u128)
+                    .checked_mul(best)
+                    .map_or(best, |scaled| scaled / best_distance(race.time).max(1));
+                let distance = match winnable {
+                    false => best,
+                    true => scaled.min(best.saturating_sub(1)),
+                };
+                Race {
+                    time,
+                    distance: distance.try_into().unwrap_or(u64::MAX),
+                }
+            })
+            .collect();
+        Some(Ok(format_races(&races)))
+    }
+
+    fn reducer(&self) -> &dyn Reducer {
+        self
     }
 }
 
+/// Removes whole races, so each time keeps its distance.
+impl Reducer for WaitForIt {
+    fn split<'a>(&self, input: &'a str, level: usize) -> Option<Split<'a>> {
+        let Ok(races) = load_races(input) else {
+            return minimize::Lines.split(input, level);
+        };
+        (level == 0).then(|| {
+            Split::new(races.len(), move |keep| {
+                format_races(&minimize::kept(&races, keep).cloned().collect::<Vec<_>>())
+            })
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
 struct Race {
     /// The race's duration, in milliseconds.
-    time: u32,
+    time: u64,
 
     /// The race's record distance, in millimeters.
-    distance: u32,
+    distance: u64,
 }
 
-impl From<(&u32, &u32)> for Race {
-    fn from(value: (&u32, &u32)) -> Self {
-        let (time, distance) = value;
-        Self {
-            time: *time,
-            distance: *distance,
+/// The longest race [`Race::count_ways_to_win`] will try every hold time of.
+const COUNTING_LIMIT: u64 = 1_000_000_000;
+
+impl Race {
+    /// Gets how far the boat travels when the button is held for the given time.
+    pub fn distance_after(&self, hold: u64) -> u128 {
+        hold as u128 * (self.time - hold) as u128
+    }
+
+    /// Counts the hold times which beat the record by trying every one of them.
+    pub fn count_ways_to_win(&self) -> Result<u64, SolveError> {
+        if self.time > COUNTING_LIMIT {
+            return Err(SolveError::Unsupported(format!(
+                "a race lasting {} milliseconds has too many hold times to try",
+                self.time
+            )));
+        }
+        let ways = (0..=self.time)
+            .filter(|&hold| self.distance_after(hold) > self.distance as u128)
+            .count();
+        Ok(ways as u64)
+    }
+
+    /// Counts the hold times which beat the record.
+    ///
+    /// Holding for `h` wins when `h * (time - h) > distance`, which is true strictly between the
+    /// roots of `h² - time * h + distance`. The roots are estimated with an integer square root
+    /// and then nudged onto the exact first winning hold time, and the winning times are
+    /// symmetric around `time / 2`.
+    pub fn ways_to_win(&self) -> u64 {
+        let time = self.time as u128;
+        let discriminant = match (time * time).checked_sub(4 * self.distance as u128) {
+            Some(d) => d,
+            None => return 0,
+        };
+
+        let mut first = ((time - discriminant.isqrt()) / 2) as u64;
+        while first <= self.time / 2 && self.distance_after(first) <= self.distance as u128 {
+            first += 1;
+        }
+        while first > 0 && self.distance_after(first - 1) > self.distance as u128 {
+            first -= 1;
+        }
+
+        if first > self.time / 2 {
+            0
+        } else {
+            self.time - 2 * first + 1
         }
     }
 }
 
-fn load_races(input: &str) -> Vec<Race> {
-    (get_times(input).and_then(parse_u32))
-        .zip(get_distances(input).and_then(parse_u32))
-        .map(|(t, d)| t.iter().zip(d.iter()).map(Race::from).collect())
-        .expect("Could not load races.")
+/// Generates a table of `size` races which can each be won, with their columns lined up.
+///
+/// Like the real races, their kerned numbers only fit in a [`u64`] when there are just a few.
+fn generate_races(rng: &mut Rng, size: usize) -> String {
+    let races: Vec<Race> = (0..size)
+        .map(|_| {
+            let time = rng.range(7..=99);
+            Race {
+                time,
+                distance: rng.range(time..=best_distance(time) as u64 - 1),
+            }
+        })
+        .collect();
+    format_races(&races)
 }
 
-const MATCH_TIME: &'static str = r"Time:((?:\s+\d+))";
+/// Gets the furthest any boat can travel in a race of the given time.
+fn best_distance(time: u64) -> u128 {
+    (time / 2) as u128 * (time - time / 2) as u128
+}
 
-fn get_times(input: &str) -> Option<&str> {
-    (Regex::new(MATCH_TIME).ok())
-        .and_then(|re| re.captures(input))
-        .and_then(|cap| cap.get(1))
-        .map(|cap| cap.as_str())
+/// Writes a table of races with their columns lined up.
+fn format_races(races: &[Race]) -> String {
+    let (mut times, mut distances) = ("Time:    ".to_string(), "Distance:".to_string());
+    for race in races {
+        let width = race.time.max(race.distance).to_string().len() + 2;
+        times += &format!("{:>width$}", race.time);
+        distances += &format!("{:>width$}", race.distance);
+    }
+    format!("{}\n{}\n", times, distances)
 }
 
-const MATCH_DISTANCE: &'static str = r"Distance:((?:\s+\d+))";
+fn load_races(input: &str) -> Result<Vec<Race>, SolveError> {
+    let (times, distances) = load_rows(input)?;
+    let (times, distances) = (parse::ints(times)?, parse::ints(distances)?);
+    if times.len() != distances.len() {
+        return Err(SolveError::Invalid(
+            "there are a different number of times and distances".to_string(),
+        ));
+    }
 
-fn get_distances(input: &str) -> Option<&str> {
-    (Regex::new(MATCH_DISTANCE).ok())
-        .and_then(|re| re.captures(input))
-        .and_then(|cap| cap.get(1))
-        .map(|cap| cap.as_str())
+    Ok((times.into_iter().zip(distances))
+        .map(|(time, distance)| Race { time, distance })
+        .collect())
 }
 
-const MATCH_U32: &'static str = r"\d+";
+/// Loads the single race described by each row once the spaces between its numbers are removed.
+fn load_kerned_race(input: &str) -> Result<Race, SolveError> {
+    let (times, distances) = load_rows(input)?;
+    let unkern = |row: &str| -> Result<u64, SolveError> {
+        let digits: String = row.chars().filter(|c| !c.is_whitespace()).collect();
+        match digits.parse::<u64>() {
+            Ok(n) => Ok(n),
+            Err(_) if digits.bytes().all(|b| b.is_ascii_digit()) => {
+                Err(SolveError::Overflow("reading the kerned race"))
+            }
+            Err(_) => Err(SolveError::Invalid(format!(
+                "{:?} is not a single number",
+                digits
+            ))),
+        }
+    };
 
-fn parse_u32(input: &str) -> Option<Vec<u32>> {
-    (Regex::new(MATCH_U32).ok()).map(|re| {
-        re.find_iter(input)
-            .filter_map(|x| x.as_str().parse().ok())
-            .collect()
+    Ok(Race {
+        time: unkern(times)?,
+        distance: unkern(distances)?,
     })
 }
+
+/// Loads the text of the time and distance rows.
+fn load_rows(input: &str) -> Result<(&str, &str), SolveError> {
+    (load_row(input, "Time"))
+        .zip(load_row(input, "Distance"))
+        .ok_or_else(|| SolveError::Invalid("could not find the times and distances".to_string()))
+}
+
+/// Loads the text after the header of the row with the given key.
+fn load_row<'a>(input: &'a str, key: &str) -> Option<&'a str> {
+    (input.lines())
+        .filter_map(parse::header)
+        .find(|(k, _)| *k == key)
+        .map(|(_, values)| values)
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::{
+        prelude::{any, prop, Strategy},
+        prop_assert, prop_assert_eq, proptest,
+    };
+
+    use crate::utils::properties;
+
+    use super::*;
+
+    const EXAMPLE: &str = "Time:      7  15   30\nDistance:  9  40  200\n";
+
+    #[test]
+    fn loads_races() {
+        assert_eq!(
+            load_races(EXAMPLE).unwrap(),
+            vec![
+                Race {
+                    time: 7,
+                    distance: 9
+                },
+                Race {
+                    time: 15,
+                    distance: 40
+                },
+                Race {
+                    time: 30,
+                    distance: 200
+                },
+            ]
+        );
+        assert_eq!(
+            load_kerned_race(EXAMPLE).unwrap(),
+            Race {
+                time: 71530,
+                distance: 940200
+            }
+        );
+        assert!(load_races("Time: 7 15\nDistance: 9\n").is_err());
+        assert!(load_races("Time: 7 15\n").is_err());
+        assert_eq!(
+            load_races("Time: 99999999999999999999 7\nDistance: 9 9\n"),
+            Err(SolveError::Overflow("reading a number"))
+        );
+    }
+
+    #[test]
+    fn counts_ways_to_win() {
+        assert_eq!(
+            Race {
+                time: 7,
+                distance: 9
+            }
+            .ways_to_win(),
+            4
+        );
+        assert_eq!(
+            Race {
+                time: 15,
+                distance: 40
+            }
+            .ways_to_win(),
+            8
+        );
+        assert_eq!(
+            Race {
+                time: 30,
+                distance: 200
+            }
+            .ways_to_win(),
+            9
+        );
+    }
+
+    #[test]
+    fn excludes_ties_with_the_record() {
+        // Holding for 2 or 4 exactly matches the record of 8.
+        assert_eq!(
+            Race {
+                time: 6,
+                distance: 8
+            }
+            .ways_to_win(),
+            1
+        );
+        assert_eq!(
+            Race {
+                time: 6,
+                distance: 9
+            }
+            .ways_to_win(),
+            0
+        );
+        assert_eq!(
+            Race {
+                time: 6,
+                distance: 100
+            }
+            .ways_to_win(),
+            0
+        );
+        assert_eq!(
+            Race {
+                time: 0,
+                distance: 0
+            }
+            .ways_to_win(),
+            0
+        );
+    }
+
+    #[test]
+    fn matches_counting_every_hold_time() {
+        for time in 0..40 {
+            for distance in 0..400 {
+                let race = Race { time, distance };
+                assert_eq!(
+                    Ok(race.ways_to_win()),
+                    race.count_ways_to_win(),
+                    "{:?}",
+                    race
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn counts_ways_to_win_long_races() {
+        let race = Race {
+            time: u64::MAX,
+            distance: u64::MAX,
+        };
+        assert_eq!(race.ways_to_win(), u64::MAX - 3);
+        assert_eq!(
+            load_kerned_race("Time: 99999 99999 99999 99999\nDistance: 1\n"),
+            Err(SolveError::Overflow("reading the kerned race"))
+        );
+    }
+
+    #[test]
+    fn generates_races_which_can_be_won() {
+        let input = WaitForIt.generate(&mut Rng::new(0), 4).unwrap();
+        let races = load_races(&input).unwrap();
+        assert_eq!(races.len(), 4);
+        assert!(races.iter().all(|race| race.ways_to_win() > 0));
+        assert!(WaitForIt.part_2(&input).is_ok());
+    }
+
+    #[test]
+    fn anonymizes_races_which_can_still_be_won() {
+        let input = "Time:      7  15   30  4\nDistance:  9  40  200  4\n";
+        let anonymized = WaitForIt
+            .anonymize(input, &mut Rng::new(0))
+            .unwrap()
+            .unwrap();
+        assert_ne!(anonymized, input);
+
+        let races = load_races(&anonymized).unwrap();
+        let winnable: Vec<bool> = races.iter().map(|race| race.ways_to_win() > 0).collect();
+        assert_eq!(winnable, [true, true, true, false]);
+        let digits: Vec<usize> = races
+            .iter()
+            .map(|race| race.time.to_string().len())
+            .collect();
+        assert_eq!(digits, [1, 2, 2, 1]);
+    }
+
+    #[test]
+    fn anonymizes_long_races_without_overflowing() {
+        let input = "Time:      123456789012345\nDistance:  9876543210987654321\n";
+        for seed in 0..20 {
+            let anonymized = WaitForIt
+                .anonymize(input, &mut Rng::new(seed))
+                .unwrap()
+                .unwrap();
+            let race = load_kerned_race(&anonymized).unwrap();
+            assert_eq!(race.time.to_string().len(), 15);
+            assert!(race.ways_to_win() > 0);
+        }
+    }
+
+    #[test]
+    fn agrees_with_counting() {
+        let input = "Time:      7  15   30\nDistance:  9  40  200\n";
+        for part in Part::ALL {
+            let results: Vec<_> = (WaitForIt.implementations(part).iter())
+                .map(|implementation| implementation.solve(input))
+                .collect();
+            assert_eq!(results.len(), 2);
+            assert_eq!(results[0], results[1]);
+        }
+
+        let long = Race {
+            time: u64::MAX,
+            distance: 0,
+        };
+        assert!(matches!(
+            long.count_ways_to_win(),
+            Err(SolveError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn minimizes_whole_races() {
+        let minimized = minimize::minimize(EXAMPLE, &WaitForIt, &mut |input| {
+            load_races(input).is_ok_and(|races| races.iter().any(|race| race.time == 15))
+        });
+        assert_eq!(minimized, "Time:      15\nDistance:  40\n");
+    }
+
+    #[test]
+    fn solves_the_example() {
+        assert_eq!(WaitForIt.part_1(EXAMPLE).unwrap(), 288_u64.into());
+        assert_eq!(WaitForIt.part_2(EXAMPLE).unwrap(), 71503_u64.into());
+    }
+
+    fn races() -> impl Strategy<Value = Race> {
+        (any::<u64>(), any::<u64>()).prop_map(|(time, distance)| Race { time, distance })
+    }
+
+    /// Races short enough to count every way to win, with records around the best distance.
+    fn short_races() -> impl Strategy<Value = Race> {
+        (0..2000_u64).prop_flat_map(|time| {
+            (0..=best_distance(time) as u64 + 10).prop_map(move |distance| Race { time, distance })
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn writes_races_which_read_back(races in prop::collection::vec(races(), 0..8)) {
+            prop_assert_eq!(load_races(&format_races(&races)).unwrap(), races);
+        }
+
+        #[test]
+        fn agrees_with_counting_short_races(race in short_races()) {
+            let ways = race.ways_to_win();
+            prop_assert_eq!(race.count_ways_to_win(), Ok(ways));
+            prop_assert!(ways <= race.time + 1);
+            prop_assert_eq!(ways == 0, race.distance as u128 >= best_distance(race.time));
+        }
+
+        #[test]
+        fn keeps_races_winnable_when_anonymizing(
+            races in prop::collection::vec(short_races(), 1..8),
+            mut rng in properties::rngs(),
+        ) {
+            let anonymized = WaitForIt.anonymize(&format_races(&races), &mut rng).unwrap().unwrap();
+            let winnable = |races: &[Race]| -> Vec<bool> {
+                races.iter().map(|race| race.ways_to_win() > 0).collect()
+            };
+            prop_assert_eq!(winnable(&load_races(&anonymized).unwrap()), winnable(&races));
+        }
+    }
+}
@@ -7,8 +7,8 @@ fn main() {
     AdventOfCode::new()
         .add_year(
             Year::new(2023)
-                .add_problem(aoc_2023::Trebuchet)
-                .add_problem(aoc_2023::CubeConundrum)
+                .add_problem(aoc_2023::Trebuchet::default())
+                .add_problem(aoc_2023::CubeConundrum::default())
                 .add_problem(aoc_2023::GearRatios)
                 .add_problem(aoc_2023::Scratchcards)
                 .add_problem(aoc_2023::IfYouGiveASeedAFertilizer)
@@ -1,11 +1,52 @@
+use std::{
+    fmt::Display,
+    fs::{self, File},
+    io::BufReader,
+    path::{Path, PathBuf},
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use clap::Parser;
 use inquire::Select;
 
-use super::year::Year;
+use super::{
+    answer::VerifiedAnswers,
+    calendar,
+    cli::{Cli, Command, Flags},
+    completion::PathHistory,
+    discrepancy,
+    error::SolveError,
+    input::Input,
+    menu::Navigation,
+    minimize,
+    parameters::Parameters,
+    problem::{Part, Problem},
+    random::Rng,
+    reference::{self, References},
+    session,
+    year::Year,
+};
 
 pub struct AdventOfCode {
     years: Vec<Year>,
 }
 
+enum Entry<'a> {
+    Year(&'a Year),
+    Calendar,
+    Quit,
+}
+
+impl Display for Entry<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Entry::Year(year) => write!(f, "{}", year.id),
+            Entry::Calendar => write!(f, "Calendar"),
+            Entry::Quit => write!(f, "Quit"),
+        }
+    }
+}
+
 impl AdventOfCode {
     pub fn new() -> Self {
         Self { years: vec![] }
@@ -16,10 +57,620 @@ impl AdventOfCode {
         self
     }
 
-    pub fn run(&self) {
-        let options = self.years.iter().map(|year| year.id).collect();
-        (Select::new("Year:", options).prompt().ok())
-            .and_then(|id| self.years.iter().find(|y| y.id == id))
-            .and_then(|y| Some(y.run()));
+    pub fn run(mut self) {
+        let cli = Cli::parse();
+
+        let params: Parameters = cli.params.into_iter().collect();
+        for year in &mut self.years {
+            if let Err(err) = year.configure(&params) {
+                eprintln!("Could not apply the parameters to {}", err);
+                return;
+            }
+        }
+
+        match cli.command {
+            None => self.prompt(&cli.flags),
+            Some(Command::Calendar) => self.calendar(),
+            Some(Command::Solve {
+                year,
+                day,
+                path,
+                part,
+                stream,
+            }) => {
+                let part = part.and_then(Part::from_number);
+                self.solve(year, day, &path, part, stream, &cli.flags)
+            }
+            Some(Command::Svg {
+                year,
+                day,
+                path,
+                output,
+            }) => self.svg(year, day, &path, &output),
+            Some(Command::Generate {
+                year,
+                day,
+                size,
+                seed,
+                output,
+            }) => self.generate(year, day, size, seed, output.as_deref()),
+            Some(Command::Anonymize {
+                year,
+                day,
+                path,
+                seed,
+                output,
+                check,
+            }) => self.anonymize(year, day, &path, seed, output.as_deref(), check),
+            Some(Command::Verify {
+                year,
+                day,
+                path,
+                part,
+                minimize,
+            }) => self.verify(
+                year,
+                day,
+                &path,
+                part.and_then(Part::from_number),
+                minimize.as_deref(),
+            ),
+            Some(Command::Bench {
+                year,
+                day,
+                path,
+                part,
+                runs,
+            }) => self.bench(year, day, &path, part.and_then(Part::from_number), runs),
+            Some(Command::Reference {
+                year,
+                day,
+                command,
+                clear,
+            }) => self.reference(year, day, command.as_deref(), clear),
+            Some(Command::Compare {
+                year,
+                day,
+                paths,
+                part,
+                generate,
+                size,
+                seed,
+            }) => {
+                let inputs = match generate {
+                    Some(count) => Inputs::Generated { count, size, seed },
+                    None => Inputs::Paths(paths),
+                };
+                self.compare(year, day, inputs, part.and_then(Part::from_number))
+            }
+            Some(Command::Query {
+                year,
+                day,
+                path,
+                query,
+            }) => self.query(year, day, &path, &query),
+        }
+    }
+
+    /// Lets the user pick years to explore until they quit.
+    fn prompt(&self, flags: &Flags) {
+        loop {
+            let mut options: Vec<Entry> = self.years.iter().map(Entry::Year).collect();
+            options.push(Entry::Calendar);
+            options.push(Entry::Quit);
+
+            match Select::new("Year:", options)
+                .prompt()
+                .map_err(Navigation::from)
+            {
+                Ok(Entry::Year(year)) => {
+                    if year.run(flags) == Navigation::Quit {
+                        return;
+                    }
+                }
+                Ok(Entry::Calendar) => self.calendar(),
+                Ok(Entry::Quit) | Err(_) => return,
+            }
+        }
+    }
+
+    /// Solves a problem directly, optionally streaming the input instead of reading it all.
+    fn solve(
+        &self,
+        year: u32,
+        day: u32,
+        path: &Path,
+        part: Option<Part>,
+        stream: bool,
+        flags: &Flags,
+    ) {
+        let Some(problem) = self.problem(year, day) else {
+            return;
+        };
+
+        let input = match stream {
+            true => None,
+            false => match read_input(path) {
+                Some(text) => Some(text),
+                None => return,
+            },
+        };
+
+        match &input {
+            Some(text) if flags.is_visualizing() => session::visualize(problem, text, flags),
+            None if flags.is_visualizing() => {
+                println!("Inputs can't be visualized while streaming.")
+            }
+            _ => (),
+        }
+        if input.is_none() && flags.explain.is_some() {
+            println!("Inputs can't be explained while streaming.");
+        }
+
+        let mut trace = input.as_ref().and_then(|_| session::open_trace(flags));
+        for part in part.map_or(Part::ALL.to_vec(), |p| vec![p]) {
+            if !problem.is_implemented(part) {
+                println!("{} is not implemented yet.", part);
+                continue;
+            }
+
+            let result = match &input {
+                Some(text) => session::solve(problem, part, text, &mut trace),
+                None => {
+                    if !problem.is_streaming(part) {
+                        println!(
+                            "{} can't be streamed, so the whole input will be read.",
+                            part
+                        );
+                    }
+                    match File::open(path) {
+                        Ok(file) => problem.solve_reader(part, &mut BufReader::new(file)),
+                        Err(err) => {
+                            eprintln!("Could not read {}: {}", path.display(), err);
+                            return;
+                        }
+                    }
+                }
+            };
+
+            match result {
+                Ok(answer) => println!("{}: {}", part, answer),
+                Err(err) => println!("{} failed. {}", part, err),
+            }
+        }
+        session::finish_trace(trace);
+    }
+
+    /// Writes the problem's SVG drawing of an input to a file.
+    fn svg(&self, year: u32, day: u32, path: &Path, output: &Path) {
+        let Some(problem) = self.problem(year, day) else {
+            return;
+        };
+        let Some(input) = read_input(path) else {
+            return;
+        };
+
+        match problem.render_svg(&input) {
+            Some(Ok(svg)) => match fs::write(output, svg) {
+                Ok(()) => eprintln!("Wrote {}.", output.display()),
+                Err(err) => eprintln!("Could not write {}: {}", output.display(), err),
+            },
+            Some(Err(err)) => eprintln!("Could not draw the input. {}", err),
+            None => eprintln!("{} can't be drawn as an SVG.", problem),
+        }
+    }
+
+    /// Writes a random input for a problem, reporting the seed if one wasn't given.
+    fn generate(&self, year: u32, day: u32, size: usize, seed: Option<u64>, output: Option<&Path>) {
+        let Some(problem) = self.problem(year, day) else {
+            return;
+        };
+
+        let Some(input) = problem.generate(&mut seeded(seed), size) else {
+            eprintln!("{} has no input generator.", problem);
+            return;
+        };
+        write_input(&input, output);
+    }
+
+    /// Writes an anonymized copy of an input, optionally checking that it still goes wrong in the
+    /// same ways.
+    fn anonymize(
+        &self,
+        year: u32,
+        day: u32,
+        path: &Path,
+        seed: Option<u64>,
+        output: Option<&Path>,
+        check: bool,
+    ) {
+        let Some(problem) = self.problem(year, day) else {
+            return;
+        };
+        let Some(input) = read_input(path) else {
+            return;
+        };
+
+        let anonymized = match problem.anonymize(&input, &mut seeded(seed)) {
+            Some(Ok(anonymized)) => anonymized,
+            Some(Err(err)) => {
+                eprintln!("Could not anonymize the input. {}", err);
+                return;
+            }
+            None => {
+                eprintln!("{} has no anonymizer.", problem);
+                return;
+            }
+        };
+
+        if check {
+            let original = discrepancy::find(problem, &input);
+            let changed = discrepancy::find(problem, &anonymized);
+            if original.is_empty() {
+                eprintln!("The original input has no discrepancies to keep.");
+            }
+            for d in &original {
+                match changed.iter().any(|c| d.is_like(c)) {
+                    true => eprintln!("Kept: {}", d),
+                    false => eprintln!("Lost: {}", d),
+                }
+            }
+            if !discrepancy::preserved(&original, &changed) {
+                eprintln!("Try another --seed to keep every discrepancy.");
+            }
+        }
+
+        write_input(&anonymized, output);
+    }
+
+    /// Solves each part with every implementation, reporting whether they agree.
+    fn verify(
+        &self,
+        year: u32,
+        day: u32,
+        path: &Path,
+        part: Option<Part>,
+        mut minimize: Option<&Path>,
+    ) {
+        let Some(problem) = self.problem(year, day) else {
+            return;
+        };
+        let Some(input) = read_input(path) else {
+            return;
+        };
+
+        for part in part.map_or(Part::ALL.to_vec(), |p| vec![p]) {
+            if !problem.is_implemented(part) {
+                println!("{} is not implemented yet.", part);
+                continue;
+            }
+
+            println!("{}:", part);
+            let mut results = vec![];
+            for implementation in problem.implementations(part) {
+                let start = Instant::now();
+                let result = implementation.solve(&input);
+                let elapsed = start.elapsed();
+                match &result {
+                    Ok(answer) => {
+                        println!("  {}: {} ({:.3?})", implementation.name, answer, elapsed)
+                    }
+                    Err(err) => println!("  {}: {}", implementation.name, err),
+                }
+                results.push((implementation.name, result));
+            }
+
+            match (results.len(), discrepancy::compare(part, &results)) {
+                (1, _) => println!("{} only has one implementation.", part),
+                (_, None)
+                    if (results.iter())
+                        .any(|(_, result)| matches!(result, Err(SolveError::Unsupported(_)))) =>
+                {
+                    println!("Every implementation which could solve the input agrees.")
+                }
+                (_, None) => println!("Every implementation agrees."),
+                (_, Some(_)) => {
+                    println!("The implementations disagree.");
+                    // Only the first part to disagree is minimized, since both would share a file.
+                    if let Some(output) = minimize.take() {
+                        minimize_disagreement(problem, part, &input, output);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Times every implementation of each part, comparing them with the fastest.
+    fn bench(&self, year: u32, day: u32, path: &Path, part: Option<Part>, runs: u32) {
+        let Some(problem) = self.problem(year, day) else {
+            return;
+        };
+        let Some(input) = read_input(path) else {
+            return;
+        };
+
+        for part in part.map_or(Part::ALL.to_vec(), |p| vec![p]) {
+            if !problem.is_implemented(part) {
+                println!("{} is not implemented yet.", part);
+                continue;
+            }
+
+            println!("{}:", part);
+            let mut timings = vec![];
+            for implementation in problem.implementations(part) {
+                let mut times = vec![];
+                let result = loop {
+                    let start = Instant::now();
+                    let result = implementation.solve(&input);
+                    times.push(start.elapsed());
+                    if result.is_err() || times.len() == runs as usize {
+                        break result;
+                    }
+                };
+
+                times.sort();
+                match result {
+                    Ok(answer) => timings.push((
+                        implementation.name,
+                        answer.to_string(),
+                        times[times.len() / 2],
+                    )),
+                    Err(err) => println!("  {}: {}", implementation.name, err),
+                }
+            }
+
+            let width = (timings.iter())
+                .map(|(name, ..)| name.len())
+                .max()
+                .unwrap_or(0);
+            let fastest = (timings.iter()).map(|&(.., median)| median).min();
+            for (name, result, median) in &timings {
+                let ratio = fastest.map_or(1.0, |fastest| {
+                    median.as_secs_f64() / fastest.as_secs_f64().max(f64::EPSILON)
+                });
+                println!(
+                    "  {:<width$}  {:>12.3?} median  {:>7.1}x  {}",
+                    name, median, ratio, result
+                );
+            }
+        }
+    }
+
+    /// Sets or clears the reference command for a day, or shows the current one.
+    fn reference(&self, year: u32, day: u32, command: Option<&str>, clear: bool) {
+        if self.problem(year, day).is_none() {
+            return;
+        }
+
+        let mut references = References::load();
+        match (command, clear) {
+            (Some(command), _) => {
+                references.set(year, day, Some(command));
+                println!(
+                    "Day {} of {} will be compared with `{}`.",
+                    day, year, command
+                );
+            }
+            (None, true) => {
+                references.set(year, day, None);
+                println!("Day {} of {} no longer has a reference command.", day, year);
+            }
+            (None, false) => match references.get(year, day) {
+                Some(command) => println!("{}", command),
+                None => println!("Day {} of {} has no reference command.", day, year),
+            },
+        }
+    }
+
+    /// Solves each input both with the problem and with the day's reference command, reporting
+    /// any answers which differ.
+    fn compare(&self, year: u32, day: u32, inputs: Inputs, part: Option<Part>) {
+        let Some(problem) = self.problem(year, day) else {
+            return;
+        };
+        let references = References::load();
+        let Some(command) = references.get(year, day) else {
+            eprintln!(
+                "Day {} of {} has no reference command. Set one with `reference` first.",
+                day, year
+            );
+            return;
+        };
+        let Some(inputs) = inputs.load(problem, year, day) else {
+            return;
+        };
+
+        let parts: Vec<Part> = (part.map_or(Part::ALL.to_vec(), |p| vec![p]).into_iter())
+            .filter(|&p| problem.is_implemented(p))
+            .collect();
+        let mut agreeing = 0;
+        for (name, input) in &inputs {
+            println!("{}:", name);
+            let stdout = match reference::run(command, input) {
+                Ok(stdout) => stdout,
+                Err(err) => {
+                    println!("  The reference command {}.", err);
+                    continue;
+                }
+            };
+
+            let mut agrees = true;
+            for &part in &parts {
+                let ours = match problem.solve(part, input) {
+                    Ok(answer) => answer.to_string(),
+                    Err(err) => format!("an error ({})", err),
+                };
+                match reference::answer(&stdout, part) {
+                    Some(theirs) if theirs == ours => println!("  {}: {}", part, ours),
+                    theirs => {
+                        agrees = false;
+                        println!(
+                            "  {}: we gave {}, but the reference gave {}",
+                            part,
+                            ours,
+                            theirs.unwrap_or("nothing")
+                        );
+                    }
+                }
+            }
+            agreeing += agrees as usize;
+        }
+        println!(
+            "{} of {} inputs agree with the reference.",
+            agreeing,
+            inputs.len()
+        );
+    }
+
+    /// Prints each line of the problem's answer to the query about the input.
+    fn query(&self, year: u32, day: u32, path: &Path, query: &str) {
+        let Some(problem) = self.problem(year, day) else {
+            return;
+        };
+        let Some(input) = read_input(path) else {
+            return;
+        };
+
+        match problem.query(&input, query) {
+            Some(Ok(lines)) => {
+                for line in lines {
+                    println!("{}", line);
+                }
+            }
+            Some(Err(err)) => eprintln!("Could not answer the query. {}", err),
+            None => eprintln!("{} can't be queried.", problem),
+        }
+    }
+
+    /// Gets the problem for the given day, reporting it if there isn't one.
+    fn problem(&self, year: u32, day: u32) -> Option<&dyn Problem> {
+        let problem = (self.years.iter())
+            .find(|y| y.id == year)
+            .and_then(|y| y.problem(day));
+        if problem.is_none() {
+            eprintln!("There is no problem for day {} of {}.", day, year);
+        }
+        problem
+    }
+
+    fn calendar(&self) {
+        let answers = VerifiedAnswers::load();
+        for year in &self.years {
+            println!("{}", calendar::render(year, &answers));
+        }
+        println!("{}", calendar::legend());
+    }
+}
+
+/// Where the inputs to compare come from.
+enum Inputs {
+    /// The given paths, or the paths used for the day before if there are none.
+    Paths(Vec<PathBuf>),
+    Generated {
+        count: usize,
+        size: usize,
+        seed: Option<u64>,
+    },
+}
+
+impl Inputs {
+    /// Reads or generates each input along with a name for it, reporting it if there are none.
+    fn load(self, problem: &dyn Problem, year: u32, day: u32) -> Option<Vec<(String, String)>> {
+        match self {
+            Inputs::Generated { count, size, seed } => {
+                let mut rng = seeded(seed);
+                let inputs: Option<Vec<_>> = (1..=count)
+                    .map(|i| {
+                        Some((
+                            format!("Generated input {}", i),
+                            problem.generate(&mut rng, size)?,
+                        ))
+                    })
+                    .collect();
+                if inputs.is_none() {
+                    eprintln!("{} has no generator.", problem);
+                }
+                inputs
+            }
+            Inputs::Paths(mut paths) => {
+                if paths.is_empty() {
+                    paths = (PathHistory::load(year, day).paths().iter())
+                        .map(PathBuf::from)
+                        .collect();
+                }
+                if paths.is_empty() {
+                    eprintln!(
+                        "No inputs have been used for day {} of {} yet. Give some paths, or \
+                         --generate some inputs.",
+                        day, year
+                    );
+                    return None;
+                }
+                Some(
+                    (paths.iter())
+                        .filter_map(|path| Some((path.display().to_string(), read_input(path)?)))
+                        .collect(),
+                )
+            }
+        }
+    }
+}
+
+/// Makes a generator from the given seed, or from a random one which is reported so that it can
+/// be reused.
+fn seeded(seed: Option<u64>) -> Rng {
+    Rng::new(seed.unwrap_or_else(|| {
+        let seed = (SystemTime::now().duration_since(UNIX_EPOCH))
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+        eprintln!("Using --seed {}.", seed);
+        seed
+    }))
+}
+
+/// Writes an input to a file, or prints it if there's no file to write to.
+fn write_input(input: &str, output: Option<&Path>) {
+    match output {
+        Some(output) => match fs::write(output, input) {
+            Ok(()) => eprintln!("Wrote {}.", output.display()),
+            Err(err) => eprintln!("Could not write {}: {}", output.display(), err),
+        },
+        None => print!("{}", input),
+    }
+}
+
+/// Shrinks the input for as long as the part's implementations still disagree on it, writing the
+/// result to a file.
+fn minimize_disagreement(problem: &dyn Problem, part: Part, input: &str, output: &Path) {
+    let mut disagrees = |input: &str| {
+        discrepancy::compare(part, &discrepancy::solve_all(problem, part, input)).is_some()
+    };
+    let minimized = minimize::minimize(input, problem.reducer(), &mut disagrees);
+    println!(
+        "Minimized the input from {} bytes to {}.",
+        input.len(),
+        minimized.len()
+    );
+    if let Some(disagreement) =
+        discrepancy::compare(part, &discrepancy::solve_all(problem, part, &minimized))
+    {
+        println!("{}.", disagreement);
+    }
+    write_input(&minimized, Some(output));
+}
+
+/// Reads and normalizes an input, printing any warnings about it or why it couldn't be read to
+/// stderr, so they never end up in an input printed to stdout.
+fn read_input(path: &Path) -> Option<String> {
+    match Input::read(path) {
+        Ok(input) => {
+            for warning in &input.warnings {
+                eprintln!("Warning: {}", warning);
+            }
+            Some(input.text)
+        }
+        Err(err) => {
+            eprintln!("Could not read {}: {}", path.display(), err);
+            None
+        }
     }
 }
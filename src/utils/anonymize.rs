@@ -0,0 +1,57 @@
+//! Helpers for rewriting inputs so they can be shared without giving away the original.
+
+use super::{parse::regex, random::Rng};
+
+/// Picks a random number with as many digits as the given one.
+///
+/// Zero stays zero, since it usually marks the start of something rather than being a value.
+pub fn number_like(rng: &mut Rng, digits: &str) -> String {
+    match digits.trim_start_matches('0') {
+        "" => digits.to_string(),
+        _ if digits.len() > 19 => (0..digits.len())
+            .map(|i| char::from(b'0' + rng.range((i == 0) as u64..=9) as u8))
+            .collect(),
+        _ => {
+            let len = digits.len() as u32;
+            let low = 10_u64.pow(len - 1);
+            let high = 10_u64.checked_pow(len).map_or(u64::MAX, |high| high - 1);
+            rng.range(low..=high).to_string()
+        }
+    }
+}
+
+/// Replaces every run of digits in the text with a random number of the same length.
+pub fn renumber(rng: &mut Rng, text: &str) -> String {
+    (regex!(r"\d+").replace_all(text, |caps: &regex::Captures| number_like(rng, &caps[0])))
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_number_of_digits() {
+        let mut rng = Rng::new(0);
+        for digits in [
+            "7",
+            "10",
+            "999",
+            "18446744073709551615",
+            "123456789012345678901",
+        ] {
+            for _ in 0..100 {
+                let number = number_like(&mut rng, digits);
+                assert_eq!(number.len(), digits.len());
+                assert!(!number.starts_with('0'));
+            }
+        }
+        assert_eq!(number_like(&mut rng, "0"), "0");
+    }
+
+    #[test]
+    fn renumbers_text() {
+        let text = renumber(&mut Rng::new(0), "Game 12: 3 red, 0 blue");
+        assert!(regex!(r"^Game [1-9]\d: [1-9] red, 0 blue$").is_match(&text));
+    }
+}
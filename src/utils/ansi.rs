@@ -0,0 +1,17 @@
+//! Escape codes for styling terminal output.
+
+use std::fmt::Display;
+
+pub const RESET: &str = "\x1b[0m";
+pub const BOLD: &str = "\x1b[1m";
+pub const DIM: &str = "\x1b[2m";
+pub const RED: &str = "\x1b[31m";
+pub const GREEN: &str = "\x1b[32m";
+pub const YELLOW: &str = "\x1b[33m";
+pub const BLUE: &str = "\x1b[34m";
+pub const CYAN: &str = "\x1b[36m";
+
+/// Wraps text in the given style, resetting the style afterwards.
+pub fn paint(style: &str, text: impl Display) -> String {
+    format!("{}{}{}", style, text, RESET)
+}
@@ -0,0 +1,62 @@
+use std::fmt::Display;
+
+use super::{problem::Part, store::Store};
+
+/// The solution to one part of a problem.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Answer(String);
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+macro_rules! impl_from_integer {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(value: $t) -> Self {
+                    Self(value.to_string())
+                }
+            }
+        )*
+    };
+}
+
+impl_from_integer!(u32, u64, u128, usize, i32, i64, i128);
+
+#[cfg(feature = "bigint")]
+impl_from_integer!(num_bigint::BigUint, num_bigint::BigInt);
+
+/// Answers which have been accepted by Advent of Code, keyed by year, day, and part.
+pub struct VerifiedAnswers {
+    store: Store,
+}
+
+impl VerifiedAnswers {
+    pub fn load() -> Self {
+        Self {
+            store: Store::open("answers"),
+        }
+    }
+
+    pub fn get(&self, year: u32, day: u32, part: Part) -> Option<Answer> {
+        (self.store.get(&key(year, day, part))).map(|a| Answer(a.to_string()))
+    }
+
+    pub fn is_verified(&self, year: u32, day: u32, part: Part) -> bool {
+        self.get(year, day, part).is_some()
+    }
+
+    /// Records the given answer as accepted.
+    pub fn verify(&mut self, year: u32, day: u32, part: Part, answer: &Answer) {
+        if let Err(err) = self.store.set(&key(year, day, part), &answer.0) {
+            println!("Could not save verified answer: {}", err);
+        }
+    }
+}
+
+fn key(year: u32, day: u32, part: Part) -> String {
+    format!("{}/{:02}/{}", year, day, part.number())
+}
@@ -0,0 +1,50 @@
+use super::{
+    ansi::{DIM, RESET, YELLOW},
+    answer::VerifiedAnswers,
+    problem::Part,
+    year::Year,
+};
+
+const DAYS: u32 = 25;
+const COLUMNS: u32 = 5;
+
+/// Renders a 25-day grid of the given year's progress.
+///
+/// Each day shows one symbol per part: a yellow `★` if the part has a verified answer, a `☆` if
+/// it has been implemented, and a `·` if it hasn't. Days without a registered problem are blank.
+pub fn render(year: &Year, answers: &VerifiedAnswers) -> String {
+    let mut out = format!("{}\n", year.id);
+
+    for day in 1..=DAYS {
+        let cell = match year.problem(day) {
+            Some(problem) => {
+                let stars: String = (Part::ALL.iter())
+                    .map(|&part| {
+                        if answers.is_verified(year.id, day, part) {
+                            format!("{}★{}", YELLOW, RESET)
+                        } else if problem.is_implemented(part) {
+                            "☆".to_string()
+                        } else {
+                            "·".to_string()
+                        }
+                    })
+                    .collect();
+                format!("{:>2} {}", day, stars)
+            }
+            None => format!("{}{:>2}   {}", DIM, day, RESET),
+        };
+
+        out.push_str(&cell);
+        out.push_str(if day % COLUMNS == 0 { "\n" } else { "   " });
+    }
+
+    out
+}
+
+/// Explains the symbols used by [`render`].
+pub fn legend() -> String {
+    format!(
+        "{}★{} verified  ☆ implemented  · not implemented",
+        YELLOW, RESET
+    )
+}
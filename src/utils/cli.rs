@@ -0,0 +1,220 @@
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand};
+
+use super::parameters;
+
+#[derive(Parser)]
+#[command(about = "Solutions to the Advent of Code problems.")]
+pub struct Cli {
+    /// Sets a parameter for the problems which use it, such as `bag=12 red, 13 green, 14 blue`.
+    #[arg(
+        long = "param",
+        value_name = "KEY=VALUE",
+        value_parser = parameters::parse_pair,
+        global = true
+    )]
+    pub params: Vec<(String, String)>,
+
+    #[command(flatten)]
+    pub flags: Flags,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Options which change how problems are run, wherever they are run from.
+#[derive(Args, Clone, Debug)]
+pub struct Flags {
+    /// Shows a visualization of each input before solving it, for the problems which have one.
+    #[arg(long, global = true)]
+    pub visualize: bool,
+
+    /// How many frames of a visualization to show per second.
+    #[arg(long, global = true, value_name = "FPS", default_value_t = 10.0)]
+    pub speed: f64,
+
+    /// Records visualizations to a GIF file instead of playing them in the terminal.
+    #[arg(long, global = true, value_name = "PATH")]
+    pub record: Option<PathBuf>,
+
+    /// Writes a trace of how each answer was reached to stderr, or to `--explain=PATH`, for the
+    /// parts which can explain themselves.
+    #[arg(
+        long,
+        global = true,
+        value_name = "PATH",
+        num_args = 0..=1,
+        require_equals = true
+    )]
+    pub explain: Option<Option<PathBuf>>,
+}
+
+impl Flags {
+    pub fn is_visualizing(&self) -> bool {
+        self.visualize || self.record.is_some()
+    }
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Shows each year's progress as a calendar.
+    Calendar,
+
+    /// Solves a problem without prompting, printing the answer to each part.
+    Solve {
+        year: u32,
+        day: u32,
+        path: PathBuf,
+
+        /// Only solves the given part.
+        #[arg(long, value_parser = clap::value_parser!(u32).range(1..=2))]
+        part: Option<u32>,
+
+        /// Reads the input one line at a time rather than loading it all at once.
+        #[arg(long)]
+        stream: bool,
+    },
+
+    /// Writes a problem's SVG drawing of an input to a file.
+    Svg {
+        year: u32,
+        day: u32,
+        path: PathBuf,
+        output: PathBuf,
+    },
+
+    /// Writes a random input for a problem, which can be shared unlike real puzzle inputs.
+    Generate {
+        #[arg(long)]
+        year: u32,
+        #[arg(long)]
+        day: u32,
+
+        /// Roughly how large the input should be, such as how many lines or cards it has.
+        #[arg(long, default_value_t = 100)]
+        size: usize,
+
+        /// Generates the same input every time. A random seed is used and reported otherwise.
+        #[arg(long)]
+        seed: Option<u64>,
+
+        /// Writes the input to a file instead of printing it.
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+
+    /// Rewrites an input so that it can be shared, keeping the shape its solution depends on.
+    Anonymize {
+        #[arg(long)]
+        year: u32,
+        #[arg(long)]
+        day: u32,
+        path: PathBuf,
+
+        /// Rewrites the input the same way every time. A random seed is used and reported
+        /// otherwise.
+        #[arg(long)]
+        seed: Option<u64>,
+
+        /// Writes the rewritten input to a file instead of printing it.
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+
+        /// Checks that the rewritten input goes wrong in the same ways as the original.
+        #[arg(long)]
+        check: bool,
+    },
+
+    /// Solves a problem with each of its implementations, reporting any which disagree.
+    Verify {
+        #[arg(long)]
+        year: u32,
+        #[arg(long)]
+        day: u32,
+        path: PathBuf,
+
+        /// Only verifies the given part.
+        #[arg(long, value_parser = clap::value_parser!(u32).range(1..=2))]
+        part: Option<u32>,
+
+        /// Shrinks the input to a minimal one the implementations still disagree on, writing it
+        /// to the given file.
+        #[arg(long, value_name = "OUTPUT")]
+        minimize: Option<PathBuf>,
+    },
+
+    /// Times each of a problem's implementations on an input, side by side.
+    Bench {
+        #[arg(long)]
+        year: u32,
+        #[arg(long)]
+        day: u32,
+        path: PathBuf,
+
+        /// Only times the given part.
+        #[arg(long, value_parser = clap::value_parser!(u32).range(1..=2))]
+        part: Option<u32>,
+
+        /// How many times to run each implementation.
+        #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u32).range(1..))]
+        runs: u32,
+    },
+
+    /// Sets the external command a day's answers are compared with, such as a teammate's solver,
+    /// or shows the current one. The command reads the input from stdin and prints the answer to
+    /// each part on its own line.
+    Reference {
+        #[arg(long)]
+        year: u32,
+        #[arg(long)]
+        day: u32,
+        command: Option<String>,
+
+        /// Removes the day's command.
+        #[arg(long, conflicts_with = "command")]
+        clear: bool,
+    },
+
+    /// Compares a problem's answers with its day's reference command, over the given inputs, the
+    /// inputs used for the day before, or generated ones.
+    Compare {
+        #[arg(long)]
+        year: u32,
+        #[arg(long)]
+        day: u32,
+        paths: Vec<PathBuf>,
+
+        /// Only compares the given part.
+        #[arg(long, value_parser = clap::value_parser!(u32).range(1..=2))]
+        part: Option<u32>,
+
+        /// Compares this many generated inputs instead.
+        #[arg(long, value_name = "COUNT", conflicts_with = "paths")]
+        generate: Option<usize>,
+
+        /// Roughly how large each generated input should be.
+        #[arg(
+            long,
+            default_value_t = 100,
+            requires = "generate",
+            conflicts_with = "paths"
+        )]
+        size: usize,
+
+        /// Generates the same inputs every time. A random seed is used and reported otherwise.
+        #[arg(long, requires = "generate", conflicts_with = "paths")]
+        seed: Option<u64>,
+    },
+
+    /// Answers a question about an input other than the puzzle's, for the problems which can, such
+    /// as `"humidity 40..50, 60 -> seed"` for day 5 of 2023.
+    Query {
+        #[arg(long)]
+        year: u32,
+        #[arg(long)]
+        day: u32,
+        path: PathBuf,
+        query: String,
+    },
+}
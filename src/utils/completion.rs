@@ -0,0 +1,161 @@
+use std::{fs, path::Path};
+
+use inquire::{autocompletion::Replacement, Autocomplete, CustomUserError};
+
+use super::store::Store;
+
+/// The number of paths remembered for each day.
+const HISTORY_LENGTH: usize = 10;
+
+/// The input paths previously used for a given day, most recent first.
+pub struct PathHistory {
+    store: Store,
+    key: String,
+    paths: Vec<String>,
+}
+
+impl PathHistory {
+    pub fn load(year: u32, day: u32) -> Self {
+        let store = Store::open("paths");
+        let key = format!("{}/{:02}", year, day);
+        let paths = store.get_list(&key);
+        Self { store, key, paths }
+    }
+
+    pub fn paths(&self) -> &[String] {
+        &self.paths
+    }
+
+    pub fn most_recent(&self) -> Option<&str> {
+        self.paths.first().map(String::as_str)
+    }
+
+    /// Moves the given path to the front of the history.
+    pub fn record(&mut self, path: &str) {
+        self.paths.retain(|p| p != path);
+        self.paths.insert(0, path.to_string());
+        self.paths.truncate(HISTORY_LENGTH);
+
+        if let Err(err) = self.store.set_list(&self.key, &self.paths) {
+            println!("Could not save path history: {}", err);
+        }
+    }
+}
+
+/// Completes filesystem paths, offering previously used paths first.
+#[derive(Clone)]
+pub struct PathCompleter {
+    history: Vec<String>,
+}
+
+impl PathCompleter {
+    pub fn new(history: &PathHistory) -> Self {
+        Self {
+            history: history.paths().to_vec(),
+        }
+    }
+}
+
+impl Autocomplete for PathCompleter {
+    fn get_suggestions(&mut self, input: &str) -> Result<Vec<String>, CustomUserError> {
+        let mut suggestions: Vec<String> = (self.history.iter())
+            .filter(|p| p.starts_with(input))
+            .cloned()
+            .collect();
+
+        if !input.is_empty() {
+            for path in list_paths(input) {
+                if !suggestions.contains(&path) {
+                    suggestions.push(path);
+                }
+            }
+        }
+
+        Ok(suggestions)
+    }
+
+    fn get_completion(
+        &mut self,
+        input: &str,
+        highlighted_suggestion: Option<String>,
+    ) -> Result<Replacement, CustomUserError> {
+        if highlighted_suggestion.is_some() {
+            return Ok(highlighted_suggestion);
+        }
+
+        let prefix = common_prefix(&list_paths(input));
+        Ok(Some(prefix).filter(|p| p.len() > input.len()))
+    }
+}
+
+/// Lists the paths which start with the given partial path. Directories end with a `/`.
+fn list_paths(input: &str) -> Vec<String> {
+    let (dir, name) = match input.rfind('/') {
+        Some(i) => input.split_at(i + 1),
+        None => ("", input),
+    };
+
+    let search = if dir.is_empty() { "." } else { dir };
+    let entries = match fs::read_dir(Path::new(search)) {
+        Ok(entries) => entries,
+        Err(_) => return vec![],
+    };
+
+    let mut paths: Vec<String> = entries
+        .filter_map(Result::ok)
+        .filter_map(|e| {
+            let file_name = e.file_name().into_string().ok()?;
+            let suffix = if e.path().is_dir() { "/" } else { "" };
+            file_name
+                .starts_with(name)
+                .then(|| format!("{}{}{}", dir, file_name, suffix))
+        })
+        .collect();
+    paths.sort();
+    paths
+}
+
+/// Finds the longest prefix shared by all of the given strings.
+fn common_prefix(values: &[String]) -> String {
+    let Some((first, rest)) = values.split_first() else {
+        return String::new();
+    };
+
+    let len = rest.iter().fold(first.chars().count(), |len, value| {
+        (first.chars().zip(value.chars()))
+            .take(len)
+            .take_while(|(a, b)| a == b)
+            .count()
+    });
+    first.chars().take(len).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_common_prefixes() {
+        assert_eq!(common_prefix(&[]), "");
+        assert_eq!(common_prefix(&["abc".to_string()]), "abc");
+        assert_eq!(
+            common_prefix(&["input_1.txt".to_string(), "input_2.txt".to_string()]),
+            "input_"
+        );
+        assert_eq!(common_prefix(&["abc".to_string(), "xyz".to_string()]), "");
+    }
+
+    #[test]
+    fn lists_matching_paths() {
+        let dir = std::env::temp_dir().join("advent_of_code_completion");
+        fs::create_dir_all(dir.join("inputs")).unwrap();
+        fs::write(dir.join("input.txt"), "").unwrap();
+
+        let dir = format!("{}/", dir.display());
+        assert_eq!(
+            list_paths(&format!("{}inp", dir)),
+            vec![format!("{}input.txt", dir), format!("{}inputs/", dir)]
+        );
+        assert!(list_paths(&format!("{}missing/", dir)).is_empty());
+    }
+}
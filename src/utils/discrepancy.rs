@@ -0,0 +1,190 @@
+//! Ways a problem can mishandle an input, which should survive any rewriting of that input.
+
+use std::{fmt::Display, mem};
+
+use super::{
+    answer::Answer,
+    error::SolveError,
+    problem::{Part, Problem},
+};
+
+/// Something wrong with how a problem handled an input.
+#[derive(Debug, PartialEq)]
+pub enum Discrepancy {
+    /// The part failed with an error.
+    Failed { part: Part, error: SolveError },
+
+    /// The implementations of the part gave different results, which are listed by name.
+    Disagreement {
+        part: Part,
+        results: Vec<(&'static str, String)>,
+    },
+
+    /// Streaming the input gave a different result than reading it all at once.
+    Streaming {
+        part: Part,
+        whole: String,
+        streamed: String,
+    },
+}
+
+impl Discrepancy {
+    /// Whether the other discrepancy is the same kind of problem with the same part, even if the
+    /// details differ.
+    pub fn is_like(&self, other: &Discrepancy) -> bool {
+        match (self, other) {
+            (Discrepancy::Failed { part, error }, Discrepancy::Failed { part: p, error: e }) => {
+                part == p && mem::discriminant(error) == mem::discriminant(e)
+            }
+            (Discrepancy::Disagreement { part, .. }, Discrepancy::Disagreement { part: p, .. })
+            | (Discrepancy::Streaming { part, .. }, Discrepancy::Streaming { part: p, .. }) => {
+                part == p
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Display for Discrepancy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Discrepancy::Failed { part, error } => write!(f, "{} failed. {}", part, error),
+            Discrepancy::Disagreement { part, results } => {
+                let results: Vec<String> = (results.iter())
+                    .map(|(name, result)| format!("{} gave {}", name, result))
+                    .collect();
+                write!(
+                    f,
+                    "{}'s implementations disagree: {}",
+                    part,
+                    results.join(", ")
+                )
+            }
+            Discrepancy::Streaming {
+                part,
+                whole,
+                streamed,
+            } => write!(
+                f,
+                "{} gave {} when reading the whole input, but {} when streaming it",
+                part, whole, streamed
+            ),
+        }
+    }
+}
+
+/// Solves each implemented part of the input in every way it can be solved, collecting anything
+/// that went wrong.
+pub fn find(problem: &dyn Problem, input: &str) -> Vec<Discrepancy> {
+    let mut discrepancies = vec![];
+    for part in Part::ALL.into_iter().filter(|&p| problem.is_implemented(p)) {
+        let mut results = solve_all(problem, part, input);
+        if results.is_empty() {
+            continue;
+        }
+        if let Some(disagreement) = compare(part, &results) {
+            discrepancies.push(disagreement);
+        }
+
+        let whole = match results.swap_remove(0) {
+            (_, Ok(answer)) => answer,
+            (_, Err(error)) => {
+                discrepancies.push(Discrepancy::Failed { part, error });
+                continue;
+            }
+        };
+
+        if problem.is_streaming(part) {
+            let streamed = match problem.solve_reader(part, &mut input.as_bytes()) {
+                Ok(answer) if answer == whole => continue,
+                Ok(answer) => answer.to_string(),
+                Err(err) => err.to_string(),
+            };
+            discrepancies.push(Discrepancy::Streaming {
+                part,
+                whole: whole.to_string(),
+                streamed,
+            });
+        }
+    }
+    discrepancies
+}
+
+/// Solves a part with each of its implementations.
+pub fn solve_all(
+    problem: &dyn Problem,
+    part: Part,
+    input: &str,
+) -> Vec<(&'static str, Result<Answer, SolveError>)> {
+    (problem.implementations(part).into_iter())
+        .map(|implementation| (implementation.name, implementation.solve(input)))
+        .collect()
+}
+
+/// Compares the results of a part's implementations, returning how they disagree if they do.
+///
+/// Implementations which don't support the input are left out.
+pub fn compare(
+    part: Part,
+    results: &[(&'static str, Result<Answer, SolveError>)],
+) -> Option<Discrepancy> {
+    let results: Vec<(&'static str, String)> = (results.iter())
+        .filter(|(_, result)| !matches!(result, Err(SolveError::Unsupported(_))))
+        .map(|(name, result)| match result {
+            Ok(answer) => (*name, answer.to_string()),
+            Err(err) => (*name, err.to_string()),
+        })
+        .collect();
+
+    match results.windows(2).all(|pair| pair[0].1 == pair[1].1) {
+        true => None,
+        false => Some(Discrepancy::Disagreement { part, results }),
+    }
+}
+
+/// Whether every original discrepancy has a counterpart among the changed ones.
+pub fn preserved(original: &[Discrepancy], changed: &[Discrepancy]) -> bool {
+    (original.iter()).all(|d| changed.iter().any(|c| d.is_like(c)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failed(part: Part, error: SolveError) -> Discrepancy {
+        Discrepancy::Failed { part, error }
+    }
+
+    #[test]
+    fn matches_discrepancies_of_the_same_kind() {
+        let overflow = failed(Part::One, SolveError::Overflow("adding"));
+        let parse = failed(Part::One, SolveError::Invalid("no seeds".to_string()));
+        assert!(overflow.is_like(&failed(Part::One, SolveError::Overflow("multiplying"))));
+        assert!(!overflow.is_like(&failed(Part::Two, SolveError::Overflow("adding"))));
+        assert!(!overflow.is_like(&parse));
+
+        assert!(preserved(&[], &[parse]));
+        assert!(!preserved(&[overflow], &[]));
+    }
+
+    #[test]
+    fn compares_implementations() {
+        let answer = |n: u32| Ok(Answer::from(n));
+        let unsupported = Err(SolveError::Unsupported("too many seeds".to_string()));
+        assert_eq!(
+            compare(Part::One, &[("a", answer(1)), ("b", answer(1))]),
+            None
+        );
+        assert_eq!(
+            compare(Part::One, &[("a", answer(1)), ("b", unsupported)]),
+            None
+        );
+        assert_eq!(
+            compare(Part::Two, &[("a", answer(1)), ("b", answer(2))]),
+            Some(Discrepancy::Disagreement {
+                part: Part::Two,
+                results: vec![("a", "1".to_string()), ("b", "2".to_string())]
+            })
+        );
+    }
+}
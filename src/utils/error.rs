@@ -0,0 +1,56 @@
+use std::fmt::Display;
+
+use super::parse::ParseError;
+
+/// Why a part couldn't produce an answer.
+#[derive(Debug, PartialEq)]
+pub enum SolveError {
+    /// Part of the input couldn't be parsed.
+    Parse(ParseError),
+
+    /// The line with the given number couldn't be parsed.
+    ParseLine(usize, ParseError),
+
+    /// The input couldn't be read.
+    Read(String),
+
+    /// The input was parsed, but doesn't describe a problem with an answer.
+    Invalid(String),
+
+    /// An intermediate value was too large for its type while computing the given thing.
+    Overflow(&'static str),
+
+    /// The implementation can't handle an input like this one, though others might.
+    Unsupported(String),
+
+    /// A query about the input couldn't be understood.
+    Query(String),
+}
+
+impl Display for SolveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SolveError::Parse(err) => write!(f, "Could not parse the input: {}", err),
+            SolveError::ParseLine(line, err) => {
+                write!(f, "Could not parse line {} of the input: {}", line, err)
+            }
+            SolveError::Read(err) => write!(f, "Could not read the input: {}", err),
+            SolveError::Invalid(reason) => write!(f, "The input is invalid: {}", reason),
+            SolveError::Overflow(computing) => {
+                write!(f, "The answer overflowed while {}", computing)
+            }
+            SolveError::Unsupported(reason) => {
+                write!(f, "This implementation can't solve the input: {}", reason)
+            }
+            SolveError::Query(reason) => write!(f, "The query is invalid: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for SolveError {}
+
+impl From<ParseError> for SolveError {
+    fn from(err: ParseError) -> Self {
+        Self::Parse(err)
+    }
+}
@@ -0,0 +1,87 @@
+//! Traces which problems can write to show how they reached an answer.
+
+use std::io::{self, Write};
+
+/// Somewhere for a problem to describe each step of its reasoning.
+pub trait Explainer {
+    /// Records a single step. The closure is only called if the trace is being kept.
+    fn explain(&mut self, step: &mut dyn FnMut() -> String);
+}
+
+/// An explainer which throws every step away without building it.
+pub struct Silent;
+
+impl Explainer for Silent {
+    fn explain(&mut self, _step: &mut dyn FnMut() -> String) {}
+}
+
+/// Writes each step on its own line, indented beneath the heading of the part it belongs to.
+///
+/// Writing stops at the first error, which is reported when the trace is finished.
+pub struct Trace<W: Write> {
+    writer: W,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> Trace<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            error: None,
+        }
+    }
+
+    /// Starts explaining a new part.
+    pub fn heading(&mut self, title: &str) {
+        self.write(format_args!("{}\n", title));
+    }
+
+    /// Flushes the trace, reporting the first error that happened while writing it.
+    pub fn finish(mut self) -> io::Result<W> {
+        if let Some(err) = self.error.take() {
+            return Err(err);
+        }
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    fn write(&mut self, args: std::fmt::Arguments) {
+        if self.error.is_none() {
+            self.error = self.writer.write_fmt(args).err();
+        }
+    }
+}
+
+impl<W: Write> Explainer for Trace<W> {
+    fn explain(&mut self, step: &mut dyn FnMut() -> String) {
+        if self.error.is_none() {
+            self.write(format_args!("  {}\n", step()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_steps_under_headings() {
+        let mut trace = Trace::new(vec![]);
+        trace.heading("Part One");
+        trace.explain(&mut || "first".to_string());
+        trace.explain(&mut || "second".to_string());
+
+        let text = String::from_utf8(trace.finish().unwrap()).unwrap();
+        assert_eq!(text, "Part One\n  first\n  second\n");
+    }
+
+    #[test]
+    fn only_builds_steps_when_tracing() {
+        let mut built = 0;
+        Silent.explain(&mut || {
+            built += 1;
+            String::new()
+        });
+        assert_eq!(built, 0);
+    }
+}
@@ -0,0 +1,327 @@
+use std::collections::VecDeque;
+
+/// A position in a [`Grid`], with `x` increasing rightwards and `y` increasing downwards.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point {
+    pub x: usize,
+    pub y: usize,
+}
+
+impl Point {
+    pub fn new(x: usize, y: usize) -> Self {
+        Self { x, y }
+    }
+}
+
+/// A horizontal run of cells in a single row.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Span {
+    pub x: usize,
+    pub y: usize,
+    pub len: usize,
+}
+
+impl Span {
+    pub fn points(&self) -> impl Iterator<Item = Point> + '_ {
+        (self.x..self.x + self.len).map(|x| Point::new(x, self.y))
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        point.y == self.y && self.x <= point.x && point.x < self.x + self.len
+    }
+}
+
+#[allow(dead_code)]
+const OFFSETS_4: [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+#[allow(dead_code)]
+const OFFSETS_8: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+/// A rectangular grid of cells, stored row by row.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl Grid<char> {
+    /// Parses a character map, padding short rows with spaces.
+    pub fn from_chars(text: &str) -> Self {
+        Self::parse(text, |c| c, ' ')
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    /// Parses a character map, converting each character into a cell.
+    ///
+    /// Rows shorter than the longest row are padded with `fill`.
+    pub fn parse(text: &str, cell: impl Fn(char) -> T, fill: T) -> Self {
+        let rows: Vec<Vec<T>> = (text.lines())
+            .map(|line| line.chars().map(&cell).collect())
+            .collect();
+        let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+
+        let mut cells = Vec::with_capacity(width * rows.len());
+        for mut row in rows.iter().cloned() {
+            row.resize(width, fill.clone());
+            cells.append(&mut row);
+        }
+
+        Self {
+            width,
+            height: rows.len(),
+            cells,
+        }
+    }
+
+    /// Gets a copy of this grid with its rows and columns swapped.
+    #[allow(dead_code)]
+    pub fn transposed(&self) -> Self {
+        Self {
+            width: self.height,
+            height: self.width,
+            cells: (0..self.width)
+                .flat_map(|x| self.column(x).cloned())
+                .collect(),
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, point: Point) -> Option<&T> {
+        self.index(point).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, point: Point) -> Option<&mut T> {
+        self.index(point).map(|i| &mut self.cells[i])
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        point.x < self.width && point.y < self.height
+    }
+
+    /// Iterates over every point in the grid, row by row.
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| Point::new(x, y)))
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(|y| self.row(y))
+    }
+
+    #[allow(dead_code)]
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        (0..self.height).map(move |y| &self.cells[y * self.width + x])
+    }
+
+    #[allow(dead_code)]
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(|x| self.column(x))
+    }
+
+    /// Iterates over the orthogonal neighbours of a point which are inside the grid.
+    #[allow(dead_code)]
+    pub fn neighbours_4(&self, point: Point) -> impl Iterator<Item = Point> + '_ {
+        self.offsets(point, &OFFSETS_4)
+    }
+
+    /// Iterates over the orthogonal and diagonal neighbours of a point which are inside the grid.
+    #[allow(dead_code)]
+    pub fn neighbours_8(&self, point: Point) -> impl Iterator<Item = Point> + '_ {
+        self.offsets(point, &OFFSETS_8)
+    }
+
+    /// Finds every maximal horizontal run of cells matching the predicate.
+    pub fn spans(&self, matches: impl Fn(&T) -> bool) -> Vec<Span> {
+        let mut spans = vec![];
+        for (y, row) in self.rows().enumerate() {
+            let mut start = None;
+            for (x, cell) in row.iter().enumerate() {
+                match (start, matches(cell)) {
+                    (None, true) => start = Some(x),
+                    (Some(s), false) => {
+                        spans.push(Span {
+                            x: s,
+                            y,
+                            len: x - s,
+                        });
+                        start = None;
+                    }
+                    _ => (),
+                }
+            }
+            if let Some(s) = start {
+                spans.push(Span {
+                    x: s,
+                    y,
+                    len: self.width - s,
+                });
+            }
+        }
+        spans
+    }
+
+    /// Iterates over the points surrounding a span, including diagonals, which are inside the
+    /// grid.
+    pub fn span_neighbours(&self, span: Span) -> impl Iterator<Item = Point> + '_ {
+        let x_min = span.x.saturating_sub(1);
+        let x_max = (span.x + span.len).min(self.width.saturating_sub(1));
+        let y_min = span.y.saturating_sub(1);
+        let y_max = (span.y + 1).min(self.height.saturating_sub(1));
+
+        (y_min..=y_max)
+            .flat_map(move |y| (x_min..=x_max).map(move |x| Point::new(x, y)))
+            .filter(move |&p| !span.contains(p) && self.contains(p))
+    }
+
+    /// Finds every point orthogonally connected to `start` through cells matching the predicate.
+    #[allow(dead_code)]
+    pub fn flood_fill(&self, start: Point, matches: impl Fn(&T) -> bool) -> Vec<Point> {
+        let mut seen = vec![false; self.cells.len()];
+        self.fill_from(start, &matches, &mut seen)
+    }
+
+    /// Groups the cells matching the predicate into orthogonally connected regions.
+    #[allow(dead_code)]
+    pub fn regions(&self, matches: impl Fn(&T) -> bool) -> Vec<Vec<Point>> {
+        let mut seen = vec![false; self.cells.len()];
+        let mut regions = vec![];
+        for point in self.points() {
+            let region = self.fill_from(point, &matches, &mut seen);
+            if !region.is_empty() {
+                regions.push(region);
+            }
+        }
+        regions
+    }
+
+    #[allow(dead_code)]
+    fn fill_from(
+        &self,
+        start: Point,
+        matches: &impl Fn(&T) -> bool,
+        seen: &mut [bool],
+    ) -> Vec<Point> {
+        let mut region = vec![];
+        let mut queue = VecDeque::from([start]);
+
+        while let Some(point) = queue.pop_front() {
+            let Some(i) = self.index(point) else {
+                continue;
+            };
+            if seen[i] || !matches(&self.cells[i]) {
+                continue;
+            }
+
+            seen[i] = true;
+            region.push(point);
+            queue.extend(self.neighbours_4(point));
+        }
+
+        region
+    }
+
+    fn index(&self, point: Point) -> Option<usize> {
+        self.contains(point)
+            .then_some(point.y * self.width + point.x)
+    }
+
+    fn offsets<'a>(
+        &'a self,
+        point: Point,
+        offsets: &'static [(isize, isize)],
+    ) -> impl Iterator<Item = Point> + 'a {
+        offsets.iter().filter_map(move |&(dx, dy)| {
+            let x = point.x.checked_add_signed(dx)?;
+            let y = point.y.checked_add_signed(dy)?;
+            Some(Point::new(x, y)).filter(|&p| self.contains(p))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_character_maps() {
+        let grid = Grid::from_chars("ab\ncde\n");
+        assert_eq!(grid.width(), 3);
+        assert_eq!(grid.height(), 2);
+        assert_eq!(grid.get(Point::new(2, 0)), Some(&' '));
+        assert_eq!(grid.get(Point::new(2, 1)), Some(&'e'));
+        assert_eq!(grid.get(Point::new(3, 1)), None);
+    }
+
+    #[test]
+    fn finds_neighbours_within_bounds() {
+        let grid = Grid::from_chars("abc\ndef\nghi");
+        let corner: Vec<Point> = grid.neighbours_4(Point::new(0, 0)).collect();
+        assert_eq!(corner, vec![Point::new(1, 0), Point::new(0, 1)]);
+        assert_eq!(grid.neighbours_8(Point::new(0, 0)).count(), 3);
+        assert_eq!(grid.neighbours_8(Point::new(1, 1)).count(), 8);
+        assert_eq!(grid.neighbours_8(Point::new(2, 2)).count(), 3);
+    }
+
+    #[test]
+    fn views_rows_and_columns() {
+        let grid = Grid::from_chars("ab\ncd");
+        assert_eq!(grid.row(1), &['c', 'd']);
+        assert_eq!(grid.column(1).collect::<String>(), "bd");
+        assert_eq!(grid.transposed(), Grid::from_chars("ac\nbd"));
+    }
+
+    #[test]
+    fn finds_spans_and_their_neighbours() {
+        let grid = Grid::from_chars("12.\n..3");
+        let spans = grid.spans(char::is_ascii_digit);
+        assert_eq!(
+            spans,
+            vec![Span { x: 0, y: 0, len: 2 }, Span { x: 2, y: 1, len: 1 }]
+        );
+
+        let around_first: Vec<Point> = grid.span_neighbours(spans[0]).collect();
+        assert_eq!(
+            around_first,
+            vec![
+                Point::new(2, 0),
+                Point::new(0, 1),
+                Point::new(1, 1),
+                Point::new(2, 1)
+            ]
+        );
+        assert_eq!(grid.span_neighbours(spans[1]).count(), 3);
+    }
+
+    #[test]
+    fn fills_regions() {
+        let grid = Grid::from_chars("aab\nbab\nbbb");
+        assert_eq!(grid.flood_fill(Point::new(0, 0), |&c| c == 'a').len(), 3);
+        assert_eq!(grid.flood_fill(Point::new(2, 0), |&c| c == 'a').len(), 0);
+        assert_eq!(grid.regions(|&c| c == 'b').len(), 1);
+        assert_eq!(grid.regions(|&c| c != '.').len(), 1);
+        assert_eq!(Grid::from_chars("a.a").regions(|&c| c == 'a').len(), 2);
+    }
+}
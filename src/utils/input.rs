@@ -0,0 +1,171 @@
+use std::{
+    fmt::Display,
+    fs,
+    io::{self, BufRead},
+    path::Path,
+};
+
+use super::error::SolveError;
+
+/// A problem input, normalized so that every problem sees the same line shapes.
+///
+/// Line endings are converted to `\n`, trailing whitespace is removed from every line, trailing
+/// blank lines are dropped, and the text ends with exactly one newline.
+pub struct Input {
+    pub text: String,
+    pub warnings: Vec<Warning>,
+}
+
+/// Something suspicious about an input which doesn't stop it from being solved.
+#[derive(Debug, PartialEq)]
+pub enum Warning {
+    Empty,
+    NonAscii {
+        count: usize,
+        first: char,
+        line: usize,
+        column: usize,
+    },
+    MissingFinalNewline,
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Warning::Empty => write!(f, "The input is empty."),
+            Warning::NonAscii {
+                count,
+                first,
+                line,
+                column,
+            } => write!(
+                f,
+                "The input contains {} non-ASCII character(s), starting with {:?} at line {}, column {}.",
+                count, first, line, column
+            ),
+            Warning::MissingFinalNewline => write!(f, "The input does not end with a newline."),
+        }
+    }
+}
+
+impl Input {
+    pub fn read(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self::normalize(&fs::read_to_string(path)?))
+    }
+
+    pub fn normalize(raw: &str) -> Self {
+        let raw = raw.strip_prefix('\u{feff}').unwrap_or(raw);
+
+        let mut lines: Vec<&str> = raw
+            .split("\r\n")
+            .flat_map(|l| l.split(['\r', '\n']))
+            .map(str::trim_end)
+            .collect();
+        while lines.last().is_some_and(|l| l.is_empty()) {
+            lines.pop();
+        }
+
+        let text: String = lines.iter().map(|l| format!("{}\n", l)).collect();
+        let warnings = validate(raw, &text);
+        Self { text, warnings }
+    }
+}
+
+/// The lines of an input, read one at a time.
+pub type Lines<'a> = dyn Iterator<Item = Result<String, SolveError>> + 'a;
+
+/// Reads an input one line at a time, so that only a single line is ever held in memory.
+///
+/// Lines are normalized like [`Input::normalize`], except that a lone `\r` doesn't end a line and
+/// trailing blank lines are kept.
+pub fn stream<'a>(
+    reader: &'a mut dyn BufRead,
+) -> impl Iterator<Item = Result<String, SolveError>> + 'a {
+    (reader.lines().enumerate()).map(|(i, line)| {
+        let line = line.map_err(|err| SolveError::Read(err.to_string()))?;
+        let line = match i {
+            0 => line.strip_prefix('\u{feff}').unwrap_or(&line),
+            _ => &line,
+        };
+        Ok(line.trim_end().to_string())
+    })
+}
+
+fn validate(raw: &str, text: &str) -> Vec<Warning> {
+    if text.is_empty() {
+        return vec![Warning::Empty];
+    }
+
+    let mut warnings = vec![];
+
+    let non_ascii = (text.lines().enumerate())
+        .flat_map(|(y, line)| line.chars().enumerate().map(move |(x, c)| (y, x, c)))
+        .filter(|(_, _, c)| !c.is_ascii());
+    let count = non_ascii.clone().count();
+    if let Some((y, x, c)) = non_ascii.take(1).next() {
+        warnings.push(Warning::NonAscii {
+            count,
+            first: c,
+            line: y + 1,
+            column: x + 1,
+        });
+    }
+
+    if !raw.ends_with('\n') {
+        warnings.push(Warning::MissingFinalNewline);
+    }
+
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_line_endings() {
+        assert_eq!(Input::normalize("a\r\nb\rc\n").text, "a\nb\nc\n");
+    }
+
+    #[test]
+    fn normalizes_trailing_whitespace() {
+        assert_eq!(Input::normalize("a  \nb\t\n\n\n").text, "a\nb\n");
+        assert_eq!(Input::normalize("a\n\nb\n").text, "a\n\nb\n");
+        assert_eq!(Input::normalize("a").text, "a\n");
+    }
+
+    #[test]
+    fn streams_normalized_lines() {
+        let mut reader = "\u{feff}a \r\nb\n\nc".as_bytes();
+        let lines: Result<Vec<String>, _> = stream(&mut reader).collect();
+        assert_eq!(lines.unwrap(), vec!["a", "b", "", "c"]);
+    }
+
+    #[test]
+    fn warns_about_empty_inputs() {
+        assert_eq!(Input::normalize("").warnings, vec![Warning::Empty]);
+        assert_eq!(Input::normalize(" \n\n").warnings, vec![Warning::Empty]);
+    }
+
+    #[test]
+    fn warns_about_non_ascii_characters() {
+        assert_eq!(
+            Input::normalize("abc\nd→e→\n").warnings,
+            vec![Warning::NonAscii {
+                count: 2,
+                first: '→',
+                line: 2,
+                column: 2,
+            }]
+        );
+    }
+
+    #[test]
+    fn warns_about_missing_final_newlines() {
+        assert_eq!(
+            Input::normalize("abc").warnings,
+            vec![Warning::MissingFinalNewline]
+        );
+        assert!(Input::normalize("abc\r\n").warnings.is_empty());
+    }
+}
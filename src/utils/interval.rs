@@ -0,0 +1,453 @@
+use std::ops::{Add, Sub};
+
+/// A half-open range of values, `start..end`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Interval<T> {
+    pub start: T,
+    pub end: T,
+}
+
+impl<T> Interval<T>
+where
+    T: Copy + Ord + Add<Output = T> + Sub<Output = T>,
+{
+    pub fn new(start: T, end: T) -> Self {
+        Self { start, end }
+    }
+
+    pub fn from_len(start: T, len: T) -> Self {
+        Self::new(start, start + len)
+    }
+
+    pub fn len(&self) -> T {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.end <= self.start
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        self.start <= value && value < self.end
+    }
+
+    /// Gets the values in both intervals, if there are any.
+    pub fn intersect(&self, other: &Self) -> Option<Self> {
+        Some(Self::new(
+            self.start.max(other.start),
+            self.end.min(other.end),
+        ))
+        .filter(|i| !i.is_empty())
+    }
+
+    /// Gets the values in this interval but not the other, as at most two intervals.
+    #[allow(dead_code)]
+    pub fn subtract(&self, other: &Self) -> Vec<Self> {
+        [
+            Self::new(self.start, self.end.min(other.start)),
+            Self::new(self.start.max(other.end), self.end),
+        ]
+        .into_iter()
+        .filter(|i| !i.is_empty())
+        .collect()
+    }
+
+    /// Splits this interval into the values before `at` and the values from `at` onwards.
+    #[allow(dead_code)]
+    pub fn split_at(&self, at: T) -> (Option<Self>, Option<Self>) {
+        let at = at.clamp(self.start, self.end);
+        let before = Self::new(self.start, at);
+        let after = Self::new(at, self.end);
+        (
+            Some(before).filter(|i| !i.is_empty()),
+            Some(after).filter(|i| !i.is_empty()),
+        )
+    }
+
+    /// Shifts this interval so that it starts at the given value.
+    pub fn moved_to(&self, start: T) -> Self {
+        Self::from_len(start, self.len())
+    }
+}
+
+/// Sorts the given intervals and merges any which overlap or touch.
+pub fn merge<T>(mut intervals: Vec<Interval<T>>) -> Vec<Interval<T>>
+where
+    T: Copy + Ord + Add<Output = T> + Sub<Output = T>,
+{
+    intervals.retain(|i| !i.is_empty());
+    intervals.sort();
+
+    let mut merged: Vec<Interval<T>> = Vec::with_capacity(intervals.len());
+    for interval in intervals {
+        match merged.last_mut() {
+            Some(last) if interval.start <= last.end => last.end = last.end.max(interval.end),
+            _ => merged.push(interval),
+        }
+    }
+    merged
+}
+
+/// A piece of a [`PiecewiseMap`], which moves its source interval to start at `dest`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Piece<T> {
+    pub source: Interval<T>,
+    pub dest: T,
+}
+
+impl<T> Piece<T>
+where
+    T: Copy + Ord + Add<Output = T> + Sub<Output = T>,
+{
+    pub fn map(&self, value: T) -> T {
+        self.dest + (value - self.source.start)
+    }
+
+    pub fn image(&self) -> Interval<T> {
+        self.source.moved_to(self.dest)
+    }
+}
+
+/// A function which moves some intervals of values by fixed offsets and leaves the rest alone.
+///
+/// Pieces are kept sorted by their source. Where sources overlap, the earliest-starting piece
+/// wins, and the others are trimmed so that no two pieces overlap.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PiecewiseMap<T> {
+    pieces: Vec<Piece<T>>,
+}
+
+impl<T> PiecewiseMap<T>
+where
+    T: Copy + Ord + Add<Output = T> + Sub<Output = T>,
+{
+    pub fn new(mut pieces: Vec<Piece<T>>) -> Self {
+        pieces.retain(|p| !p.source.is_empty());
+        pieces.sort_by_key(|p| p.source);
+
+        // Every earlier piece starts before this one, so the part of it they cover is a prefix.
+        let mut disjoint: Vec<Piece<T>> = vec![];
+        for piece in pieces {
+            let start = (disjoint.last()).map_or(piece.source.start, |last| {
+                piece.source.start.max(last.source.end)
+            });
+            if start < piece.source.end {
+                disjoint.push(Piece {
+                    source: Interval::new(start, piece.source.end),
+                    dest: piece.map(start),
+                });
+            }
+        }
+        Self { pieces: disjoint }
+    }
+
+    pub fn pieces(&self) -> &[Piece<T>] {
+        &self.pieces
+    }
+
+    pub fn map(&self, value: T) -> T {
+        (self.pieces.iter())
+            .find(|p| p.source.contains(value))
+            .map_or(value, |p| p.map(value))
+    }
+
+    /// Maps every value in the interval, giving the intervals they end up in.
+    pub fn map_interval(&self, interval: Interval<T>) -> Vec<Interval<T>> {
+        (self.segments(interval).into_iter())
+            .map(|(segment, piece)| match piece {
+                Some(piece) => segment.moved_to(piece.map(segment.start)),
+                None => segment,
+            })
+            .collect()
+    }
+
+    /// Maps every value in the intervals, giving the merged intervals they end up in.
+    pub fn map_intervals(&self, intervals: &[Interval<T>]) -> Vec<Interval<T>> {
+        merge(
+            (intervals.iter())
+                .flat_map(|&i| self.map_interval(i))
+                .collect(),
+        )
+    }
+
+    /// Finds every value which maps into the given intervals, as merged intervals.
+    pub fn preimage(&self, intervals: &[Interval<T>]) -> Vec<Interval<T>> {
+        let mut preimage = vec![];
+        for interval in intervals {
+            for piece in &self.pieces {
+                if let Some(overlap) = piece.image().intersect(interval) {
+                    let start = piece.source.start + (overlap.start - piece.dest);
+                    preimage.push(overlap.moved_to(start));
+                }
+            }
+            preimage.extend(self.unmapped(*interval));
+        }
+        merge(preimage)
+    }
+
+    /// Gets the map which applies this map and then the next one.
+    pub fn then(&self, next: &Self) -> Self {
+        let mut pieces = vec![];
+
+        for piece in &self.pieces {
+            let image = piece.image();
+            for (segment, next_piece) in next.segments(image) {
+                let source = segment.moved_to(piece.source.start + (segment.start - image.start));
+                let dest = next_piece.map_or(segment.start, |p| p.map(segment.start));
+                pieces.push(Piece { source, dest });
+            }
+        }
+        for next_piece in &next.pieces {
+            for source in self.unmapped(next_piece.source) {
+                pieces.push(Piece {
+                    source,
+                    dest: next_piece.map(source.start),
+                });
+            }
+        }
+
+        Self::new(pieces).simplified()
+    }
+
+    /// Gets the inverse of this map, if it is a bijection.
+    ///
+    /// A map is a bijection when its pieces' images cover exactly the same values as their
+    /// sources, so that no two values map to the same place.
+    pub fn invert(&self) -> Option<Self> {
+        let mut images: Vec<Interval<T>> = self.pieces.iter().map(Piece::image).collect();
+        images.sort();
+        let disjoint = images.windows(2).all(|w| w[0].end <= w[1].start);
+        let sources = merge(self.pieces.iter().map(|p| p.source).collect());
+
+        (disjoint && merge(images) == sources).then(|| {
+            Self::new(
+                (self.pieces.iter())
+                    .map(|p| Piece {
+                        source: p.image(),
+                        dest: p.source.start,
+                    })
+                    .collect(),
+            )
+        })
+    }
+
+    /// Splits the interval into segments which are each either inside a single piece or outside
+    /// every piece.
+    fn segments(&self, interval: Interval<T>) -> Vec<(Interval<T>, Option<&Piece<T>>)> {
+        let mut segments = vec![];
+        let mut cursor = interval.start;
+
+        for piece in &self.pieces {
+            let Some(overlap) = Interval::new(cursor, interval.end).intersect(&piece.source) else {
+                continue;
+            };
+            if cursor < overlap.start {
+                segments.push((Interval::new(cursor, overlap.start), None));
+            }
+            segments.push((overlap, Some(piece)));
+            cursor = overlap.end;
+        }
+        if cursor < interval.end {
+            segments.push((Interval::new(cursor, interval.end), None));
+        }
+
+        segments
+    }
+
+    /// Gets the parts of the interval which no piece applies to.
+    fn unmapped(&self, interval: Interval<T>) -> Vec<Interval<T>> {
+        (self.segments(interval).into_iter())
+            .filter(|(_, piece)| piece.is_none())
+            .map(|(segment, _)| segment)
+            .collect()
+    }
+
+    /// Drops pieces which don't move their values and joins neighbouring pieces which move their
+    /// values by the same offset.
+    fn simplified(self) -> Self {
+        let mut pieces: Vec<Piece<T>> = vec![];
+        for piece in self.pieces {
+            if piece.source.start == piece.dest {
+                continue;
+            }
+            match pieces.last_mut() {
+                Some(last)
+                    if last.source.end == piece.source.start && last.image().end == piece.dest =>
+                {
+                    last.source.end = piece.source.end;
+                }
+                _ => pieces.push(piece),
+            }
+        }
+        Self { pieces }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn i(start: u64, end: u64) -> Interval<u64> {
+        Interval::new(start, end)
+    }
+
+    #[test]
+    fn intersects_intervals() {
+        assert_eq!(i(0, 10).intersect(&i(5, 15)), Some(i(5, 10)));
+        assert_eq!(i(0, 10).intersect(&i(10, 15)), None);
+        assert_eq!(i(0, 10).intersect(&i(2, 3)), Some(i(2, 3)));
+    }
+
+    #[test]
+    fn subtracts_intervals() {
+        assert_eq!(i(0, 10).subtract(&i(3, 5)), vec![i(0, 3), i(5, 10)]);
+        assert_eq!(i(0, 10).subtract(&i(0, 5)), vec![i(5, 10)]);
+        assert_eq!(i(0, 10).subtract(&i(20, 30)), vec![i(0, 10)]);
+        assert_eq!(i(0, 10).subtract(&i(0, 10)), vec![]);
+    }
+
+    #[test]
+    fn splits_intervals() {
+        assert_eq!(i(0, 10).split_at(4), (Some(i(0, 4)), Some(i(4, 10))));
+        assert_eq!(i(0, 10).split_at(0), (None, Some(i(0, 10))));
+        assert_eq!(i(0, 10).split_at(15), (Some(i(0, 10)), None));
+    }
+
+    #[test]
+    fn merges_intervals() {
+        assert_eq!(
+            merge(vec![i(5, 8), i(0, 3), i(3, 4), i(7, 10), i(12, 12)]),
+            vec![i(0, 4), i(5, 10)]
+        );
+    }
+
+    #[test]
+    fn maps_values_at_piece_boundaries() {
+        let map = PiecewiseMap::new(vec![Piece {
+            source: i(98, 100),
+            dest: 50,
+        }]);
+        assert_eq!(map.map(97), 97);
+        assert_eq!(map.map(98), 50);
+        assert_eq!(map.map(99), 51);
+        assert_eq!(map.map(100), 100);
+    }
+
+    #[test]
+    fn maps_intervals_through_pieces() {
+        let map = PiecewiseMap::new(vec![
+            Piece {
+                source: i(98, 100),
+                dest: 50,
+            },
+            Piece {
+                source: i(50, 98),
+                dest: 52,
+            },
+        ]);
+        assert_eq!(
+            map.map_interval(i(40, 105)),
+            vec![i(40, 50), i(52, 100), i(50, 52), i(100, 105)]
+        );
+        assert_eq!(map.map_intervals(&[i(40, 105)]), vec![i(40, 105)]);
+        assert_eq!(map.map_intervals(&[i(79, 93)]), vec![i(81, 95)]);
+    }
+
+    #[test]
+    fn composes_maps() {
+        let first = PiecewiseMap::new(vec![Piece {
+            source: i(0, 10),
+            dest: 100,
+        }]);
+        let second = PiecewiseMap::new(vec![
+            Piece {
+                source: i(105, 110),
+                dest: 0,
+            },
+            Piece {
+                source: i(20, 30),
+                dest: 40,
+            },
+        ]);
+        let composed = first.then(&second);
+
+        for value in 0..120 {
+            assert_eq!(composed.map(value), second.map(first.map(value)));
+        }
+        assert_eq!(
+            composed.pieces(),
+            &[
+                Piece {
+                    source: i(0, 5),
+                    dest: 100
+                },
+                Piece {
+                    source: i(5, 10),
+                    dest: 0
+                },
+                Piece {
+                    source: i(20, 30),
+                    dest: 40
+                },
+                Piece {
+                    source: i(105, 110),
+                    dest: 0
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn composes_maps_with_overlapping_pieces() {
+        let piece = |start, end, dest| Piece {
+            source: i(start, end),
+            dest,
+        };
+        let first = PiecewiseMap::new(vec![piece(0, 10, 50), piece(5, 20, 0), piece(5, 8, 90)]);
+        let second = PiecewiseMap::new(vec![piece(45, 55, 200), piece(50, 70, 5), piece(0, 3, 60)]);
+        let composed = first.then(&second);
+
+        for value in 0..100 {
+            assert_eq!(composed.map(value), second.map(first.map(value)));
+        }
+        assert_eq!(first.map(6), 56);
+        assert_eq!(first.map(12), 7);
+        let sources: Vec<Interval<u64>> = first.pieces().iter().map(|p| p.source).collect();
+        assert_eq!(sources, vec![i(0, 10), i(10, 20)]);
+    }
+
+    #[test]
+    fn inverts_bijections() {
+        let map = PiecewiseMap::new(vec![
+            Piece {
+                source: i(0, 5),
+                dest: 5,
+            },
+            Piece {
+                source: i(5, 10),
+                dest: 0,
+            },
+        ]);
+        let inverse = map.invert().unwrap();
+        for value in 0..20 {
+            assert_eq!(inverse.map(map.map(value)), value);
+        }
+
+        let collapsing = PiecewiseMap::new(vec![Piece {
+            source: i(0, 5),
+            dest: 5,
+        }]);
+        assert_eq!(collapsing.invert(), None);
+    }
+
+    #[test]
+    fn finds_preimages() {
+        let map = PiecewiseMap::new(vec![Piece {
+            source: i(0, 5),
+            dest: 5,
+        }]);
+        assert_eq!(map.preimage(&[i(5, 7)]), vec![i(0, 2), i(5, 7)]);
+        assert_eq!(map.preimage(&[i(0, 5)]), vec![]);
+        assert_eq!(map.preimage(&[i(12, 15)]), vec![i(12, 15)]);
+    }
+}
@@ -0,0 +1,55 @@
+use std::fmt::Display;
+
+use inquire::{InquireError, Select};
+
+/// How the user chose to leave a menu.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Navigation {
+    Back,
+    Quit,
+}
+
+impl From<InquireError> for Navigation {
+    fn from(err: InquireError) -> Self {
+        match err {
+            InquireError::OperationCanceled => Navigation::Back,
+            InquireError::OperationInterrupted => Navigation::Quit,
+            err => {
+                println!("{}", err);
+                Navigation::Quit
+            }
+        }
+    }
+}
+
+enum Choice<T> {
+    Item(T),
+    Back,
+    Quit,
+}
+
+impl<T: Display> Display for Choice<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Choice::Item(item) => item.fmt(f),
+            Choice::Back => write!(f, "Back"),
+            Choice::Quit => write!(f, "Quit"),
+        }
+    }
+}
+
+/// Prompts the user to pick one of the given items.
+///
+/// "Back" and "Quit" are offered alongside the items. Pressing Esc goes back and pressing Ctrl-C
+/// quits.
+pub fn choose<T: Display>(message: &str, items: Vec<T>) -> Result<T, Navigation> {
+    let mut options: Vec<Choice<T>> = items.into_iter().map(Choice::Item).collect();
+    options.push(Choice::Back);
+    options.push(Choice::Quit);
+
+    match Select::new(message, options).prompt()? {
+        Choice::Item(item) => Ok(item),
+        Choice::Back => Err(Navigation::Back),
+        Choice::Quit => Err(Navigation::Quit),
+    }
+}
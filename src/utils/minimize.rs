@@ -0,0 +1,158 @@
+//! Delta debugging, which shrinks an input while it keeps going wrong in the same way.
+
+/// Puts an input back together from the pieces marked to keep.
+type Rebuild<'a> = dyn Fn(&[bool]) -> String + 'a;
+
+/// An input taken apart into pieces which can be removed, and a way of putting it back together
+/// without some of them.
+pub struct Split<'a> {
+    pub count: usize,
+    rebuild: Box<Rebuild<'a>>,
+}
+
+impl<'a> Split<'a> {
+    pub fn new(count: usize, rebuild: impl Fn(&[bool]) -> String + 'a) -> Self {
+        Self {
+            count,
+            rebuild: Box::new(rebuild),
+        }
+    }
+
+    /// Puts the input back together with only the pieces marked to keep.
+    pub fn rebuild(&self, keep: &[bool]) -> String {
+        (self.rebuild)(keep)
+    }
+}
+
+/// Knows which pieces of a problem's input can be removed without it becoming unreadable.
+pub trait Reducer {
+    /// Takes the input apart at the given level of detail, starting from the coarsest at zero,
+    /// or returns `None` past the finest level.
+    fn split<'a>(&self, input: &'a str, level: usize) -> Option<Split<'a>>;
+}
+
+/// Removes whole lines, and then the words in each line.
+pub struct Lines;
+
+impl Reducer for Lines {
+    fn split<'a>(&self, input: &'a str, level: usize) -> Option<Split<'a>> {
+        let lines: Vec<&str> = input.lines().filter(|l| !l.trim().is_empty()).collect();
+        match level {
+            0 => Some(Split::new(lines.len(), move |keep| {
+                join_lines(kept(&lines, keep))
+            })),
+            1 => {
+                let words: Vec<Vec<&str>> = (lines.iter())
+                    .map(|line| line.split_whitespace().collect())
+                    .collect();
+                Some(Split::new(words.iter().map(Vec::len).sum(), move |keep| {
+                    let mut keep = keep.iter();
+                    join_lines((words.iter()).map(|line| {
+                        (line.iter())
+                            .filter(|_| *keep.next().unwrap_or(&true))
+                            .copied()
+                            .collect::<Vec<_>>()
+                            .join(" ")
+                    }))
+                }))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Gets the items which are marked to keep.
+pub fn kept<'a, T>(items: &'a [T], keep: &'a [bool]) -> impl Iterator<Item = &'a T> {
+    (items.iter().zip(keep)).filter_map(|(item, &keep)| keep.then_some(item))
+}
+
+/// Puts lines back together, leaving out any which ended up empty.
+pub fn join_lines<T: AsRef<str>>(lines: impl Iterator<Item = T>) -> String {
+    (lines.filter(|line| !line.as_ref().trim().is_empty()))
+        .map(|line| format!("{}\n", line.as_ref()))
+        .collect()
+}
+
+/// Shrinks the input for as long as it keeps failing the same way, going through each level of
+/// the reducer until no single piece at any level can be removed.
+pub fn minimize(input: &str, reducer: &dyn Reducer, fails: &mut dyn FnMut(&str) -> bool) -> String {
+    let mut current = input.to_string();
+    loop {
+        let before = current.clone();
+        for level in 0.. {
+            let Some(split) = reducer.split(&current, level) else {
+                break;
+            };
+            let keep = ddmin(split.count, &mut |keep| fails(&split.rebuild(keep)));
+            let reduced = split.rebuild(&keep);
+            drop(split);
+            // Rebuilding can tidy the input even when nothing was removed, so check it still fails.
+            if reduced != current && fails(&reduced) {
+                current = reduced;
+            }
+        }
+        if current == before {
+            return current;
+        }
+    }
+}
+
+/// Finds a set of pieces to keep where removing any single one would stop the failure, by
+/// removing ever smaller chunks of the pieces which are left.
+fn ddmin(count: usize, fails: &mut dyn FnMut(&[bool]) -> bool) -> Vec<bool> {
+    let mut keep = vec![true; count];
+    let mut chunks = 2;
+    loop {
+        let left: Vec<usize> = (0..count).filter(|&i| keep[i]).collect();
+        if left.is_empty() {
+            return keep;
+        }
+
+        let size = left.len().div_ceil(chunks.min(left.len()));
+        let removed = left.chunks(size).find_map(|chunk| {
+            let mut candidate = keep.clone();
+            for &i in chunk {
+                candidate[i] = false;
+            }
+            fails(&candidate).then_some(candidate)
+        });
+
+        match removed {
+            Some(candidate) => {
+                keep = candidate;
+                chunks = (chunks - 1).max(2);
+            }
+            None if size == 1 => return keep,
+            None => chunks = (chunks * 2).min(left.len()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_the_smallest_set_that_fails() {
+        // Fails whenever both 3 and 7 are kept.
+        let keep = ddmin(10, &mut |keep| keep[3] && keep[7]);
+        let left: Vec<usize> = (0..10).filter(|&i| keep[i]).collect();
+        assert_eq!(left, vec![3, 7]);
+
+        assert_eq!(ddmin(4, &mut |_| true), vec![false; 4]);
+        assert_eq!(ddmin(0, &mut |_| true), Vec::<bool>::new());
+    }
+
+    #[test]
+    fn minimizes_lines_and_then_words() {
+        let input = "a b c\nd e bad f\ng h\n\ni bad\n";
+        let minimized = minimize(input, &Lines, &mut |text| text.contains("bad"));
+        assert_eq!(minimized, "bad\n");
+
+        let minimized = minimize(input, &Lines, &mut |text| text.lines().count() == 2);
+        assert_eq!(minimized.lines().count(), 2);
+        assert!(minimized
+            .lines()
+            .all(|line| line.split_whitespace().count() == 1));
+    }
+}
@@ -1,9 +1,43 @@
 mod advent_of_code;
+pub mod anonymize;
+pub mod ansi;
+mod answer;
+mod calendar;
+mod cli;
+mod completion;
+mod discrepancy;
+mod error;
+pub mod explain;
+pub mod grid;
+mod input;
+pub mod interval;
+mod menu;
+pub mod minimize;
+pub mod num;
+mod parameters;
+pub mod parse;
 mod problem;
+#[cfg(test)]
+pub mod properties;
+pub mod random;
+mod reference;
+mod session;
+mod store;
+pub mod visualizer;
 mod year;
 
 pub mod prelude {
     pub use super::advent_of_code::AdventOfCode;
-    pub use super::problem::Problem;
+    pub use super::answer::Answer;
+    pub use super::error::SolveError;
+    pub use super::explain::{Explainer, Silent};
+    pub use super::input::Lines;
+    pub use super::num::{self, CheckedIterator, Total};
+    pub use super::parameters::Parameters;
+    pub(crate) use super::parse::regex;
+    pub use super::parse::{self, FromInput, ParseError};
+    pub use super::problem::{Implementation, Part, Problem};
+    pub use super::random::Rng;
+    pub use super::visualizer::{Color, Frame, Visualizer};
     pub use super::year::Year;
 }
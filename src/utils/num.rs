@@ -0,0 +1,85 @@
+//! Overflow-checked arithmetic for accumulating answers.
+
+use num_traits::{CheckedAdd, CheckedMul, One, Zero};
+
+use super::error::SolveError;
+
+/// The integer type which answers are accumulated in.
+///
+/// With the `bigint` feature this is arbitrary-precision, so it can never overflow.
+#[cfg(not(feature = "bigint"))]
+pub type Total = u64;
+
+/// The integer type which answers are accumulated in.
+///
+/// With the `bigint` feature this is arbitrary-precision, so it can never overflow.
+#[cfg(feature = "bigint")]
+pub type Total = num_bigint::BigUint;
+
+/// An integer which answers can be computed in without silently wrapping.
+pub trait Num: Clone + Zero + One + CheckedAdd + CheckedMul {}
+
+impl<T: Clone + Zero + One + CheckedAdd + CheckedMul> Num for T {}
+
+/// Adds two numbers, failing with a description of what was being computed if they overflow.
+pub fn add<T: Num>(a: &T, b: &T, computing: &'static str) -> Result<T, SolveError> {
+    a.checked_add(b).ok_or(SolveError::Overflow(computing))
+}
+
+/// Multiplies two numbers, failing with a description of what was being computed if they
+/// overflow.
+pub fn mul<T: Num>(a: &T, b: &T, computing: &'static str) -> Result<T, SolveError> {
+    a.checked_mul(b).ok_or(SolveError::Overflow(computing))
+}
+
+/// Raises a number to a power, failing with a description of what was being computed if it
+/// overflows.
+pub fn pow<T: Num>(base: T, exp: usize, computing: &'static str) -> Result<T, SolveError> {
+    num_traits::checked_pow(base, exp).ok_or(SolveError::Overflow(computing))
+}
+
+/// Sums and multiplies iterators without silently wrapping.
+pub trait CheckedIterator: Iterator + Sized
+where
+    Self::Item: Num,
+{
+    fn checked_sum(mut self, computing: &'static str) -> Result<Self::Item, SolveError> {
+        self.try_fold(Self::Item::zero(), |sum, x| add(&sum, &x, computing))
+    }
+
+    fn checked_product(mut self, computing: &'static str) -> Result<Self::Item, SolveError> {
+        self.try_fold(Self::Item::one(), |product, x| mul(&product, &x, computing))
+    }
+}
+
+impl<I: Iterator> CheckedIterator for I where I::Item: Num {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sums_without_wrapping() {
+        assert_eq!([1_u8, 2, 3].into_iter().checked_sum("testing"), Ok(6));
+        assert_eq!(
+            [200_u8, 100].into_iter().checked_sum("testing"),
+            Err(SolveError::Overflow("testing"))
+        );
+        assert_eq!(
+            [i64::MIN, -1].into_iter().checked_sum("testing"),
+            Err(SolveError::Overflow("testing"))
+        );
+    }
+
+    #[test]
+    fn multiplies_without_wrapping() {
+        assert_eq!(
+            Vec::<u32>::new().into_iter().checked_product("testing"),
+            Ok(1)
+        );
+        assert_eq!([16_u8, 15].into_iter().checked_product("testing"), Ok(240));
+        assert!([16_u8, 16].into_iter().checked_product("testing").is_err());
+        assert_eq!(pow(2_u128, 127, "testing"), Ok(1 << 127));
+        assert!(pow(2_u128, 128, "testing").is_err());
+    }
+}
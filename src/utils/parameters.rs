@@ -0,0 +1,71 @@
+use std::{collections::BTreeMap, fmt::Display, str::FromStr};
+
+/// Named values given on the command line which change how problems are solved.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Parameters(BTreeMap<String, String>);
+
+impl Parameters {
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(String::as_str)
+    }
+
+    /// Parses the value of the given parameter, if it was given.
+    pub fn parse<T>(&self, key: &str) -> Result<Option<T>, String>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        (self.get(key))
+            .map(|value| {
+                (value.parse())
+                    .map_err(|err| format!("{:?} is not a valid {}: {}", value, key, err))
+            })
+            .transpose()
+    }
+}
+
+impl FromIterator<(String, String)> for Parameters {
+    fn from_iter<I: IntoIterator<Item = (String, String)>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+/// Parses a `key=value` command line argument.
+pub fn parse_pair(arg: &str) -> Result<(String, String), String> {
+    match arg.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.trim().to_string()))
+        }
+        _ => Err(format!("{:?} should look like `key=value`", arg)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_pairs() {
+        assert_eq!(
+            parse_pair("bag = 1 red, 2 blue"),
+            Ok(("bag".to_string(), "1 red, 2 blue".to_string()))
+        );
+        assert_eq!(
+            parse_pair("empty="),
+            Ok(("empty".to_string(), "".to_string()))
+        );
+        assert!(parse_pair("bag").is_err());
+        assert!(parse_pair("=1").is_err());
+    }
+
+    #[test]
+    fn parses_values() {
+        let params: Parameters = [("size".to_string(), "12".to_string())]
+            .into_iter()
+            .collect();
+        assert_eq!(params.parse::<u32>("size"), Ok(Some(12)));
+        assert_eq!(params.parse::<u32>("missing"), Ok(None));
+        assert!(params.parse::<i8>("size").is_ok());
+        assert!(params.parse::<bool>("size").is_err());
+    }
+}
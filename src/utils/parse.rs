@@ -0,0 +1,320 @@
+use std::{fmt::Display, str::FromStr};
+
+pub use advent_of_code_derive::FromInput;
+use regex::Captures;
+
+use super::error::SolveError;
+
+/// Compiles a regex the first time it is used and reuses it on every later call.
+///
+/// Each invocation site gets its own cache, so the pattern must be the same every time the site
+/// is reached.
+macro_rules! regex {
+    ($pattern:expr) => {{
+        static RE: std::sync::OnceLock<regex::Regex> = std::sync::OnceLock::new();
+        RE.get_or_init(|| regex::Regex::new($pattern).expect("Pattern was not a valid regex."))
+    }};
+}
+
+pub(crate) use regex;
+
+/// An integer type that can be extracted from text by [`ints`].
+pub trait Int: FromStr {
+    const SIGNED: bool;
+}
+
+macro_rules! impl_int {
+    ($signed:expr => $($t:ty),*) => {
+        $(
+            impl Int for $t {
+                const SIGNED: bool = $signed;
+            }
+        )*
+    };
+}
+
+impl_int!(false => u8, u16, u32, u64, u128, usize);
+impl_int!(true => i8, i16, i32, i64, i128, isize);
+
+#[cfg(feature = "bigint")]
+impl_int!(false => num_bigint::BigUint);
+
+#[cfg(feature = "bigint")]
+impl_int!(true => num_bigint::BigInt);
+
+/// Extracts every integer in the given text, in order.
+///
+/// Signed types treat a `-` directly before a number as its sign, while unsigned types treat it as
+/// a separator. A number which doesn't fit in the type is an overflow.
+pub fn ints<T: Int>(text: &str) -> Result<Vec<T>, SolveError> {
+    let re = if T::SIGNED {
+        regex!(r"-?\d+")
+    } else {
+        regex!(r"\d+")
+    };
+
+    (re.find_iter(text))
+        .map(|m| (m.as_str().parse()).map_err(|_| SolveError::Overflow("reading a number")))
+        .collect()
+}
+
+/// Parses every non-empty line of the given text, reporting the number of the first bad line.
+pub fn lines<T>(text: &str) -> Result<Vec<T>, SolveError>
+where
+    T: FromStr<Err = ParseError>,
+{
+    (text.lines().enumerate())
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            line.parse()
+                .map_err(|err| SolveError::ParseLine(i + 1, err))
+        })
+        .collect()
+}
+
+/// Parses every non-empty line as it is read, reporting the number of each bad line.
+pub fn stream<T>(
+    lines: impl Iterator<Item = Result<String, SolveError>>,
+) -> impl Iterator<Item = Result<T, SolveError>>
+where
+    T: FromStr<Err = ParseError>,
+{
+    (lines.enumerate()).filter_map(|(i, line)| match line {
+        Ok(line) if line.trim().is_empty() => None,
+        Ok(line) => Some(
+            line.parse()
+                .map_err(|err| SolveError::ParseLine(i + 1, err)),
+        ),
+        Err(err) => Some(Err(err)),
+    })
+}
+
+/// Splits the given text into sections separated by blank lines.
+pub fn sections(text: &str) -> Vec<&str> {
+    (regex!(r"\n[ \t]*\n").split(text))
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// Splits a `key: values` header into its key and values.
+pub fn header(text: &str) -> Option<(&str, &str)> {
+    (text.split_once(':')).map(|(key, values)| (key.trim(), values.trim()))
+}
+
+/// Splits a line into columns of the given width, trimming each column.
+///
+/// The last column may be shorter than the others.
+#[allow(dead_code)]
+pub fn columns(line: &str, width: usize) -> Vec<&str> {
+    let mut columns = vec![];
+    let mut rest = line;
+    while !rest.is_empty() {
+        let end = (rest.char_indices())
+            .nth(width)
+            .map_or(rest.len(), |(i, _)| i);
+        let (column, tail) = rest.split_at(end);
+        columns.push(column.trim());
+        rest = tail;
+    }
+    columns
+}
+
+/// A line which couldn't be parsed into the value it was supposed to describe.
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    Mismatch {
+        target: &'static str,
+        pattern: &'static str,
+        line: String,
+    },
+    MissingField {
+        target: &'static str,
+        field: &'static str,
+        line: String,
+    },
+    InvalidField {
+        target: &'static str,
+        field: &'static str,
+        value: String,
+        reason: String,
+        line: String,
+    },
+}
+
+impl ParseError {
+    pub fn mismatch(target: &'static str, pattern: &'static str, line: &str) -> Self {
+        Self::Mismatch {
+            target,
+            pattern,
+            line: line.to_string(),
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::Mismatch {
+                target,
+                pattern,
+                line,
+            } => write!(
+                f,
+                "{:?} is not a {}, which should match `{}`",
+                line, target, pattern
+            ),
+            ParseError::MissingField {
+                target,
+                field,
+                line,
+            } => write!(f, "{:?} is missing the {} of a {}", line, field, target),
+            ParseError::InvalidField {
+                target,
+                field,
+                value,
+                reason,
+                line,
+            } => write!(
+                f,
+                "{:?} has an invalid {} for a {}: {:?} ({})",
+                line, field, target, value, reason
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// The named captures of a line, as used by `#[derive(FromInput)]`.
+pub struct Fields<'a> {
+    target: &'static str,
+    line: &'a str,
+    captures: &'a Captures<'a>,
+}
+
+impl<'a> Fields<'a> {
+    pub fn new(target: &'static str, line: &'a str, captures: &'a Captures<'a>) -> Self {
+        Self {
+            target,
+            line,
+            captures,
+        }
+    }
+
+    /// Parses the given capture group.
+    pub fn get<T>(&self, field: &'static str) -> Result<T, ParseError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        match self.optional(field)? {
+            Some(value) => Ok(value),
+            None => Err(ParseError::MissingField {
+                target: self.target,
+                field,
+                line: self.line.to_string(),
+            }),
+        }
+    }
+
+    /// Parses the given capture group, if it participated in the match.
+    pub fn optional<T>(&self, field: &'static str) -> Result<Option<T>, ParseError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        (self.captures.name(field))
+            .map(|m| self.parse(field, m.as_str().trim()))
+            .transpose()
+    }
+
+    /// Parses each item in the given capture group.
+    ///
+    /// Items are separated by whitespace and commas unless another separator is given.
+    pub fn list<T>(
+        &self,
+        field: &'static str,
+        separator: Option<&str>,
+    ) -> Result<Vec<T>, ParseError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let text = (self.captures.name(field)).map_or("", |m| m.as_str());
+        let items: Vec<&str> = match separator {
+            Some(separator) => text.split(separator).map(str::trim).collect(),
+            None => regex!(r"[\s,]+").split(text).collect(),
+        };
+
+        (items.into_iter())
+            .filter(|item| !item.is_empty())
+            .map(|item| self.parse(field, item))
+            .collect()
+    }
+
+    fn parse<T>(&self, field: &'static str, value: &str) -> Result<T, ParseError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        value
+            .parse()
+            .map_err(|err: T::Err| ParseError::InvalidField {
+                target: self.target,
+                field,
+                value: value.to_string(),
+                reason: err.to_string(),
+                line: self.line.to_string(),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_unsigned_ints() {
+        assert_eq!(ints::<u32>(""), Ok(vec![]));
+        assert_eq!(ints::<u32>("Card 12: 3 45 | 6"), Ok(vec![12, 3, 45, 6]));
+        assert_eq!(ints::<u8>("1-2, 255"), Ok(vec![1, 2, 255]));
+        assert_eq!(
+            ints::<u8>("1-2, 300"),
+            Err(SolveError::Overflow("reading a number"))
+        );
+        assert_eq!(
+            ints::<u128>("340282366920938463463374607431768211455"),
+            Ok(vec![u128::MAX])
+        );
+    }
+
+    #[test]
+    fn extracts_signed_ints() {
+        assert_eq!(ints::<i64>("x=-3, y=4"), Ok(vec![-3, 4]));
+        assert_eq!(ints::<i8>("-128 127"), Ok(vec![-128, 127]));
+        assert!(ints::<i8>("-128 128").is_err());
+    }
+
+    #[test]
+    fn splits_sections() {
+        assert_eq!(sections("a\nb\n\nc\n  \nd\n"), vec!["a\nb", "c", "d"]);
+        assert_eq!(sections("\n\n"), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn splits_headers() {
+        assert_eq!(header("Time:   7  15"), Some(("Time", "7  15")));
+        assert_eq!(
+            header("seed-to-soil map:\n1 2 3"),
+            Some(("seed-to-soil map", "1 2 3"))
+        );
+        assert_eq!(header("no header"), None);
+    }
+
+    #[test]
+    fn splits_columns() {
+        assert_eq!(columns("[A] [B]     [D]", 4), vec!["[A]", "[B]", "", "[D]"]);
+        assert_eq!(columns("", 4), Vec::<&str>::new());
+    }
+}
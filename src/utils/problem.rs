@@ -1,28 +1,181 @@
-use std::fs;
+use std::{fmt::Display, io::BufRead};
 
-use inquire::{Select, Text};
+use super::{
+    answer::Answer,
+    error::SolveError,
+    explain::Explainer,
+    input::{self, Input, Lines},
+    minimize::{self, Reducer},
+    parameters::Parameters,
+    random::Rng,
+    visualizer::Visualizer,
+};
 
-const PART_1: &'static str = "Part One";
-const PART_2: &'static str = "Part Two";
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub const ALL: [Part; 2] = [Part::One, Part::Two];
+
+    pub fn number(&self) -> u32 {
+        match self {
+            Part::One => 1,
+            Part::Two => 2,
+        }
+    }
+
+    pub fn from_number(number: u32) -> Option<Part> {
+        Part::ALL.into_iter().find(|p| p.number() == number)
+    }
+}
+
+impl Display for Part {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Part::One => write!(f, "Part One"),
+            Part::Two => write!(f, "Part Two"),
+        }
+    }
+}
+
+/// Solves a part from its input.
+type Solver<'a> = dyn Fn(&str) -> Result<Answer, SolveError> + 'a;
+
+/// A named way of solving a part, which can be checked against the others.
+pub struct Implementation<'a> {
+    pub name: &'static str,
+    solve: Box<Solver<'a>>,
+}
+
+impl<'a> Implementation<'a> {
+    pub fn new(
+        name: &'static str,
+        solve: impl Fn(&str) -> Result<Answer, SolveError> + 'a,
+    ) -> Self {
+        Self {
+            name,
+            solve: Box::new(solve),
+        }
+    }
+
+    pub fn solve(&self, input: &str) -> Result<Answer, SolveError> {
+        (self.solve)(input)
+    }
+}
 
 pub trait Problem {
     fn id(&self) -> &str;
-    fn part_1(&self, input: &str);
-    fn part_2(&self, input: &str);
+    fn day(&self) -> u32;
+    fn part_1(&self, input: &str) -> Result<Answer, SolveError>;
+    fn part_2(&self, input: &str) -> Result<Answer, SolveError>;
 
-    fn run(&self) {
-        let options = vec![PART_1, PART_2];
+    /// Emits frames showing how the input is interpreted, or returns `None` if this problem has no
+    /// visualization.
+    fn visualize(
+        &self,
+        _input: &str,
+        _visualizer: &mut dyn Visualizer,
+    ) -> Option<Result<(), SolveError>> {
+        None
+    }
 
-        let part = Select::new("Part:", options)
-            .prompt()
-            .expect("Something went wrong.");
-        let path = Text::new("Path:").prompt().expect("Something went wrong.");
-        let file = fs::read_to_string(path).expect("File not found.");
+    /// Draws the input as an SVG image, or `None` if this problem has no drawing.
+    fn render_svg(&self, _input: &str) -> Option<Result<String, SolveError>> {
+        None
+    }
 
+    /// Solves a part while explaining each step to the explainer, or returns `None` if the part
+    /// can't explain itself.
+    fn explain(
+        &self,
+        _part: Part,
+        _input: &str,
+        _explainer: &mut dyn Explainer,
+    ) -> Option<Result<Answer, SolveError>> {
+        None
+    }
+
+    /// Generates a random input of roughly the given size, or returns `None` if this problem has no
+    /// generator.
+    fn generate(&self, _rng: &mut Rng, _size: usize) -> Option<String> {
+        None
+    }
+
+    /// Rewrites the input so that it can be shared, keeping the shape its solution depends on, or
+    /// returns `None` if this problem has no anonymizer.
+    fn anonymize(&self, _input: &str, _rng: &mut Rng) -> Option<Result<String, SolveError>> {
+        None
+    }
+
+    /// Answers a question about the input other than the puzzle's own, with a line for each thing
+    /// it found, or returns `None` if this problem can't be queried.
+    fn query(&self, _input: &str, _query: &str) -> Option<Result<Vec<String>, SolveError>> {
+        None
+    }
+
+    /// Knows which pieces of the input can be removed while minimizing it. By default whole lines
+    /// are removed, and then the words in each line.
+    fn reducer(&self) -> &dyn Reducer {
+        &minimize::Lines
+    }
+
+    /// Applies the runtime parameters this problem uses, ignoring any others.
+    fn configure(&mut self, _params: &Parameters) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Whether the given part has been implemented yet.
+    fn is_implemented(&self, _part: Part) -> bool {
+        true
+    }
+
+    fn solve(&self, part: Part, input: &str) -> Result<Answer, SolveError> {
         match part {
-            PART_1 => self.part_1(file.as_str()),
-            PART_2 => self.part_2(file.as_str()),
-            _ => (),
-        };
+            Part::One => self.part_1(input),
+            Part::Two => self.part_2(input),
+        }
+    }
+
+    /// Every way this problem can solve the given part, starting with the one [`Problem::solve`]
+    /// uses.
+    ///
+    /// Problems with slower but simpler implementations, such as brute force searches, can list
+    /// them here so that they can be checked against each other.
+    fn implementations(&self, part: Part) -> Vec<Implementation<'_>> {
+        vec![Implementation::new("default", move |input| {
+            self.solve(part, input)
+        })]
+    }
+
+    /// Whether the given part reads its input one line at a time, using constant memory.
+    fn is_streaming(&self, _part: Part) -> bool {
+        false
+    }
+
+    /// Solves a part from the lines of its input.
+    ///
+    /// Parts which only ever need a few lines at a time should override this along with
+    /// [`Problem::is_streaming`]. By default every line is collected before solving.
+    fn solve_lines(&self, part: Part, lines: &mut Lines) -> Result<Answer, SolveError> {
+        let mut text = String::new();
+        for line in lines {
+            text.push_str(&line?);
+            text.push('\n');
+        }
+        self.solve(part, &Input::normalize(&text).text)
+    }
+
+    /// Solves a part while reading its input line by line.
+    fn solve_reader(&self, part: Part, reader: &mut dyn BufRead) -> Result<Answer, SolveError> {
+        self.solve_lines(part, &mut input::stream(reader))
+    }
+}
+
+impl Display for dyn Problem + '_ {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.id())
     }
 }
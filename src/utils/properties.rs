@@ -0,0 +1,46 @@
+//! Helpers for the property tests each day declares with [`proptest`].
+
+use std::{
+    fmt::{Debug, Display},
+    str::FromStr,
+};
+
+use proptest::{
+    prelude::{any, Strategy},
+    prop_assert, prop_assert_eq,
+    test_runner::TestCaseError,
+};
+
+use super::{
+    discrepancy,
+    problem::{Part, Problem},
+    random::Rng,
+};
+
+/// Generators seeded from anything, so problems' own input generators can be explored.
+pub fn rngs() -> impl Strategy<Value = Rng> {
+    any::<u64>().prop_map(Rng::new)
+}
+
+/// Checks that writing the value out and parsing it back gives the same value.
+pub fn round_trips<T>(value: &T) -> Result<(), TestCaseError>
+where
+    T: Display + FromStr + PartialEq + Debug,
+    T::Err: Debug,
+{
+    let text = value.to_string();
+    match text.parse::<T>() {
+        Ok(parsed) => prop_assert_eq!(&parsed, value, "{:?} parsed differently", text),
+        Err(err) => prop_assert!(false, "{:?} could not be parsed: {:?}", text, err),
+    }
+    Ok(())
+}
+
+/// Checks that every implementation of the part which can solve the input gives the same answer.
+pub fn agrees(problem: &dyn Problem, part: Part, input: &str) -> Result<(), TestCaseError> {
+    let results = discrepancy::solve_all(problem, part, input);
+    if let Some(disagreement) = discrepancy::compare(part, &results) {
+        prop_assert!(false, "{} on {:?}", disagreement, input);
+    }
+    Ok(())
+}
@@ -0,0 +1,113 @@
+//! A small seeded random number generator, so generated inputs are the same on every machine.
+
+use std::ops::RangeInclusive;
+
+/// A SplitMix64 generator.
+///
+/// The same seed always produces the same numbers, unlike generators from crates which are free
+/// to change their algorithms between versions.
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Picks a number in the range, with every number equally likely.
+    pub fn range(&mut self, range: RangeInclusive<u64>) -> u64 {
+        let (start, end) = range.into_inner();
+        assert!(start <= end, "Range was empty.");
+        let span = (end - start).wrapping_add(1);
+        if span == 0 {
+            return self.next_u64();
+        }
+
+        // Rejecting the uneven remainder keeps small numbers from being more likely.
+        let zone = u64::MAX - u64::MAX % span;
+        loop {
+            let value = self.next_u64();
+            if value < zone {
+                return start + value % span;
+            }
+        }
+    }
+
+    /// Picks an index below the given length.
+    pub fn index(&mut self, len: usize) -> usize {
+        assert!(len > 0, "Can't pick from nothing.");
+        self.range(0..=len as u64 - 1) as usize
+    }
+
+    /// Returns true with the given probability.
+    pub fn chance(&mut self, probability: f64) -> bool {
+        let unit = (self.next_u64() >> 11) as f64 / (1_u64 << 53) as f64;
+        unit < probability
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.index(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.index(i + 1));
+        }
+    }
+
+    /// Picks `count` different numbers from the range, in no particular order.
+    pub fn distinct(&mut self, range: RangeInclusive<u64>, count: usize) -> Vec<u64> {
+        let mut pool: Vec<u64> = range.collect();
+        assert!(count <= pool.len(), "Range was too small.");
+        for i in 0..count {
+            let j = i + self.index(pool.len() - i);
+            pool.swap(i, j);
+        }
+        pool.truncate(count);
+        pool
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeats_sequences_for_the_same_seed() {
+        let mut a = Rng::new(7);
+        let mut b = Rng::new(7);
+        let mut c = Rng::new(8);
+        let a: Vec<u64> = (0..4).map(|_| a.next_u64()).collect();
+        assert_eq!(a, (0..4).map(|_| b.next_u64()).collect::<Vec<_>>());
+        assert_ne!(a, (0..4).map(|_| c.next_u64()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn stays_within_ranges() {
+        let mut rng = Rng::new(0);
+        for _ in 0..1000 {
+            assert!((3..=5).contains(&rng.range(3..=5)));
+        }
+        assert_eq!(rng.range(9..=9), 9);
+        rng.range(0..=u64::MAX);
+    }
+
+    #[test]
+    fn picks_distinct_numbers() {
+        let mut rng = Rng::new(0);
+        let mut numbers = rng.distinct(1..=10, 10);
+        numbers.sort();
+        assert_eq!(numbers, (1..=10).collect::<Vec<_>>());
+        assert_eq!(rng.distinct(1..=99, 25).len(), 25);
+    }
+}
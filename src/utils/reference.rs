@@ -0,0 +1,115 @@
+//! External commands which solve a day some other way, such as a teammate's solver, for checking
+//! answers against.
+
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+    thread,
+};
+
+use super::{problem::Part, store::Store};
+
+/// The external command configured for each day, keyed by year and day.
+pub struct References {
+    store: Store,
+}
+
+impl References {
+    pub fn load() -> Self {
+        Self {
+            store: Store::open("references"),
+        }
+    }
+
+    pub fn get(&self, year: u32, day: u32) -> Option<&str> {
+        self.store.get(&key(year, day))
+    }
+
+    /// Sets the command for the given day, or removes it if there is none.
+    pub fn set(&mut self, year: u32, day: u32, command: Option<&str>) {
+        let saved = match command {
+            Some(command) => self.store.set(&key(year, day), command),
+            None => self.store.remove(&key(year, day)),
+        };
+        if let Err(err) = saved {
+            eprintln!("Could not save the reference command: {}", err);
+        }
+    }
+}
+
+fn key(year: u32, day: u32) -> String {
+    format!("{}/{:02}", year, day)
+}
+
+/// Runs the command through the shell with the input on its stdin, returning what it printed or
+/// why it failed.
+pub fn run(command: &str, input: &str) -> Result<String, String> {
+    let mut child = shell(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("could not be started: {}", err))?;
+
+    // Writing from another thread keeps a command which prints before reading everything from
+    // filling its stdout while we're still filling its stdin.
+    let mut stdin = child.stdin.take().expect("stdin was piped");
+    let input = input.to_string();
+    let writer = thread::spawn(move || stdin.write_all(input.as_bytes()));
+
+    let output = (child.wait_with_output()).map_err(|err| format!("could not be run: {}", err))?;
+    // Commands which don't read their input close stdin early, which is fine.
+    let _ = writer.join();
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    match (output.status.success(), stderr.trim()) {
+        (true, _) => Ok(String::from_utf8_lossy(&output.stdout).into_owned()),
+        (false, "") => Err(format!("exited with {}", output.status)),
+        (false, stderr) => Err(format!("exited with {}: {}", output.status, stderr)),
+    }
+}
+
+#[cfg(unix)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.arg("/C").arg(command);
+    shell
+}
+
+/// Picks a part's answer out of what a reference command printed.
+///
+/// Each non-empty line answers the next part, and only its last word is the answer, so lines such
+/// as `Part 1: 288` work too.
+pub fn answer(stdout: &str, part: Part) -> Option<&str> {
+    (stdout.lines())
+        .filter(|line| !line.trim().is_empty())
+        .nth(part.number() as usize - 1)
+        .and_then(|line| line.split_whitespace().last())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_answers_out_of_each_line() {
+        assert_eq!(answer("288\n71503\n", Part::One), Some("288"));
+        assert_eq!(answer("288\n\n71503\n", Part::Two), Some("71503"));
+        assert_eq!(answer("Part 1: 288\n", Part::One), Some("288"));
+        assert_eq!(answer("Part 1: 288\n", Part::Two), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn runs_commands_with_the_input() {
+        assert_eq!(run("wc -l", "a\nb\n").unwrap().trim(), "2");
+        assert!(run("exit 3", "").unwrap_err().contains("exit status: 3"));
+    }
+}
@@ -0,0 +1,234 @@
+use std::{
+    fmt::Display,
+    fs::File,
+    io::{self, Write},
+};
+
+use inquire::{Confirm, Text};
+
+use super::{
+    answer::{Answer, VerifiedAnswers},
+    cli::Flags,
+    completion::{PathCompleter, PathHistory},
+    error::SolveError,
+    explain::{Explainer, Trace},
+    input::Input,
+    menu::{self, Navigation},
+    problem::{Part, Problem},
+    visualizer::Recording,
+};
+
+/// An interactive session with a single problem.
+///
+/// The input is loaded the first time a part is run and is reused until the user changes it.
+pub struct Session<'a> {
+    year: u32,
+    problem: &'a dyn Problem,
+    flags: &'a Flags,
+    input: Option<String>,
+    last: Option<Action>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Action {
+    Run(Part),
+    RunBoth,
+    Rerun(Option<Part>),
+    ChangeInput,
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Action::Run(part) => write!(f, "{}", part),
+            Action::RunBoth => write!(f, "Both Parts"),
+            Action::Rerun(Some(part)) => write!(f, "Re-run {}", part),
+            Action::Rerun(None) => write!(f, "Re-run Both Parts"),
+            Action::ChangeInput => write!(f, "Change Input"),
+        }
+    }
+}
+
+impl<'a> Session<'a> {
+    pub fn new(year: u32, problem: &'a dyn Problem, flags: &'a Flags) -> Self {
+        Self {
+            year,
+            problem,
+            flags,
+            input: None,
+            last: None,
+        }
+    }
+
+    /// Runs the session until the user goes back or quits.
+    pub fn run(&mut self) -> Navigation {
+        loop {
+            let mut actions = vec![];
+            match self.last {
+                Some(Action::Run(part)) => actions.push(Action::Rerun(Some(part))),
+                Some(Action::RunBoth) => actions.push(Action::Rerun(None)),
+                _ => (),
+            }
+            actions.extend(Part::ALL.map(Action::Run));
+            actions.push(Action::RunBoth);
+            if self.input.is_some() {
+                actions.push(Action::ChangeInput);
+            }
+
+            let action = match menu::choose(self.problem.id(), actions) {
+                Ok(action) => action,
+                Err(nav) => return nav,
+            };
+
+            let result = match action {
+                Action::Run(part) | Action::Rerun(Some(part)) => {
+                    self.last = Some(Action::Run(part));
+                    self.solve(&[part])
+                }
+                Action::RunBoth | Action::Rerun(None) => {
+                    self.last = Some(Action::RunBoth);
+                    self.solve(&Part::ALL)
+                }
+                Action::ChangeInput => self.load_input().map(|_| ()),
+            };
+
+            if let Err(Navigation::Quit) = result {
+                return Navigation::Quit;
+            }
+        }
+    }
+
+    fn solve(&mut self, parts: &[Part]) -> Result<(), Navigation> {
+        let input = match &self.input {
+            Some(input) => input.clone(),
+            None => self.load_input()?,
+        };
+
+        if self.flags.is_visualizing() {
+            visualize(self.problem, &input, self.flags);
+        }
+
+        let mut trace = open_trace(self.flags);
+        for &part in parts {
+            if !self.problem.is_implemented(part) {
+                println!("{} is not implemented yet.", part);
+                continue;
+            }
+
+            let answer = match solve(self.problem, part, &input, &mut trace) {
+                Ok(answer) => answer,
+                Err(err) => {
+                    println!("{} failed. {}", part, err);
+                    continue;
+                }
+            };
+            println!("{}: {}", part, answer);
+
+            let mut answers = VerifiedAnswers::load();
+            match answers.get(self.year, self.problem.day(), part) {
+                Some(verified) if verified == answer => println!("Matches the verified answer."),
+                Some(verified) => println!("Differs from the verified answer ({}).", verified),
+                None => {
+                    let accepted = Confirm::new("Was this answer accepted?")
+                        .with_default(false)
+                        .prompt()?;
+                    if accepted {
+                        answers.verify(self.year, self.problem.day(), part, &answer);
+                    }
+                }
+            }
+        }
+        finish_trace(trace);
+
+        Ok(())
+    }
+
+    /// Prompts for an input file and caches its normalized contents.
+    ///
+    /// The most recently used path for this day is offered as the default.
+    fn load_input(&mut self) -> Result<String, Navigation> {
+        let mut history = PathHistory::load(self.year, self.problem.day());
+        loop {
+            let completer = PathCompleter::new(&history);
+            let mut prompt = Text::new("Path:").with_autocomplete(completer);
+            if let Some(path) = history.most_recent() {
+                prompt = prompt.with_default(path);
+            }
+
+            let path = prompt.prompt()?;
+            match Input::read(&path) {
+                Ok(input) => {
+                    history.record(&path);
+                    for warning in &input.warnings {
+                        println!("Warning: {}", warning);
+                    }
+                    self.input = Some(input.text.clone());
+                    return Ok(input.text);
+                }
+                Err(err) => println!("Could not read {}: {}", path, err),
+            }
+        }
+    }
+}
+
+/// Plays the problem's visualization of the input, or records it to a file if asked to.
+pub fn visualize(problem: &dyn Problem, input: &str, flags: &Flags) {
+    let mut recording = Recording::default();
+    match problem.visualize(input, &mut recording) {
+        Some(Ok(())) => match &flags.record {
+            Some(path) => match recording.save_gif(path, flags.speed) {
+                Ok(()) => println!(
+                    "Wrote {} frame(s) to {}.",
+                    recording.frames.len(),
+                    path.display()
+                ),
+                Err(err) => println!("Could not write {}: {}", path.display(), err),
+            },
+            None => recording.replay(flags.speed),
+        },
+        Some(Err(err)) => println!("Could not visualize the input. {}", err),
+        None => println!("{} has no visualization.", problem),
+    }
+}
+
+/// A trace of explained parts, written wherever `--explain` asked for it.
+pub type Sink = Trace<Box<dyn Write>>;
+
+/// Opens the trace asked for by `--explain`, if any.
+pub fn open_trace(flags: &Flags) -> Option<Sink> {
+    let writer: Box<dyn Write> = match flags.explain.as_ref()? {
+        Some(path) => match File::create(path) {
+            Ok(file) => Box::new(io::BufWriter::new(file)),
+            Err(err) => {
+                eprintln!("Could not write {}: {}", path.display(), err);
+                return None;
+            }
+        },
+        None => Box::new(io::stderr()),
+    };
+    Some(Trace::new(writer))
+}
+
+/// Solves a part, explaining it to the trace if there is one and the part can explain itself.
+pub fn solve(
+    problem: &dyn Problem,
+    part: Part,
+    input: &str,
+    trace: &mut Option<Sink>,
+) -> Result<Answer, SolveError> {
+    if let Some(trace) = trace {
+        trace.heading(&format!("{}, {}", problem, part));
+        match problem.explain(part, input, trace) {
+            Some(result) => return result,
+            None => trace.explain(&mut || "This part can't explain itself.".to_string()),
+        }
+    }
+    problem.solve(part, input)
+}
+
+/// Finishes writing the trace, reporting anything that went wrong while writing it.
+pub fn finish_trace(trace: Option<Sink>) {
+    if let Some(Err(err)) = trace.map(Trace::finish) {
+        eprintln!("Could not write the explanation: {}", err);
+    }
+}
@@ -0,0 +1,67 @@
+use std::{collections::BTreeMap, fs, io, path::PathBuf};
+
+/// The directory, relative to the working directory, that stores are kept in.
+const STORE_DIR: &str = ".advent_of_code";
+
+/// A persistent key-value file for state that should outlive a single run.
+///
+/// Each entry is stored on its own line as `key<TAB>value`. Lists are stored as tab-separated
+/// values.
+pub struct Store {
+    path: PathBuf,
+    entries: BTreeMap<String, String>,
+}
+
+impl Store {
+    /// Opens the store with the given name, or an empty one if it doesn't exist yet.
+    pub fn open(name: &str) -> Self {
+        let path = PathBuf::from(STORE_DIR).join(name);
+        let entries = fs::read_to_string(&path)
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|l| l.split_once('\t'))
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+
+        Self { path, entries }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries.get(key).map(String::as_str)
+    }
+
+    pub fn get_list(&self, key: &str) -> Vec<String> {
+        (self.get(key).into_iter())
+            .flat_map(|v| v.split('\t'))
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Sets the value of the given key and writes the store back to disk.
+    pub fn set(&mut self, key: &str, value: &str) -> io::Result<()> {
+        self.entries.insert(key.to_string(), value.to_string());
+        self.save()
+    }
+
+    /// Sets the list of values of the given key and writes the store back to disk.
+    pub fn set_list(&mut self, key: &str, values: &[String]) -> io::Result<()> {
+        self.set(key, &values.join("\t"))
+    }
+
+    /// Removes the given key and writes the store back to disk.
+    pub fn remove(&mut self, key: &str) -> io::Result<()> {
+        self.entries.remove(key);
+        self.save()
+    }
+
+    fn save(&self) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let contents: String = (self.entries.iter())
+            .map(|(k, v)| format!("{}\t{}\n", k, v))
+            .collect();
+        fs::write(&self.path, contents)
+    }
+}
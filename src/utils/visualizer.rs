@@ -0,0 +1,293 @@
+//! Frames which problems can emit to show how they work, and ways to play them back.
+
+use std::{fs::File, io, path::Path, thread, time::Duration};
+
+use super::{
+    ansi::{self, paint},
+    grid::{Grid, Point},
+};
+
+/// The colors a cell can be drawn in, both in the terminal and in recordings.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Color {
+    #[default]
+    Default,
+    Dim,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Cyan,
+    BoldGreen,
+    BoldYellow,
+}
+
+impl Color {
+    const ALL: [Color; 9] = [
+        Color::Default,
+        Color::Dim,
+        Color::Red,
+        Color::Green,
+        Color::Yellow,
+        Color::Blue,
+        Color::Cyan,
+        Color::BoldGreen,
+        Color::BoldYellow,
+    ];
+
+    /// Gets the escape codes which draw text in this color, or `None` for the terminal's default.
+    pub fn ansi(&self) -> Option<String> {
+        let style = match self {
+            Color::Default => return None,
+            Color::Dim => ansi::DIM.to_string(),
+            Color::Red => ansi::RED.to_string(),
+            Color::Green => ansi::GREEN.to_string(),
+            Color::Yellow => ansi::YELLOW.to_string(),
+            Color::Blue => ansi::BLUE.to_string(),
+            Color::Cyan => ansi::CYAN.to_string(),
+            Color::BoldGreen => format!("{}{}", ansi::BOLD, ansi::GREEN),
+            Color::BoldYellow => format!("{}{}", ansi::BOLD, ansi::YELLOW),
+        };
+        Some(style)
+    }
+
+    fn rgb(&self) -> [u8; 3] {
+        match self {
+            Color::Default => [0xd0, 0xd0, 0xd0],
+            Color::Dim => [0x60, 0x60, 0x60],
+            Color::Red => [0xe1, 0x57, 0x59],
+            Color::Green | Color::BoldGreen => [0x59, 0xa1, 0x4f],
+            Color::Yellow | Color::BoldYellow => [0xed, 0xc9, 0x48],
+            Color::Blue => [0x4e, 0x79, 0xa7],
+            Color::Cyan => [0x76, 0xb7, 0xb2],
+        }
+    }
+}
+
+/// A single colored character in a frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cell {
+    pub ch: char,
+    pub color: Color,
+}
+
+impl Cell {
+    pub fn new(ch: char, color: Color) -> Self {
+        Self { ch, color }
+    }
+}
+
+/// A snapshot of a grid of colored characters, with an optional caption underneath.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Frame {
+    pub grid: Grid<Cell>,
+    pub caption: Option<String>,
+}
+
+impl Frame {
+    /// Creates a frame of uncolored characters.
+    pub fn from_text(text: &str) -> Self {
+        Self {
+            grid: Grid::parse(
+                text,
+                |c| Cell::new(c, Color::Default),
+                Cell::new(' ', Color::Default),
+            ),
+            caption: None,
+        }
+    }
+
+    pub fn paint(&mut self, point: Point, color: Color) {
+        if let Some(cell) = self.grid.get_mut(point) {
+            cell.color = color;
+        }
+    }
+
+    pub fn with_caption(mut self, caption: impl Into<String>) -> Self {
+        self.caption = Some(caption.into());
+        self
+    }
+
+    /// Renders the frame with escape codes for the terminal.
+    pub fn to_ansi(&self) -> String {
+        let mut out = String::new();
+        for row in self.grid.rows() {
+            for cell in row {
+                match cell.color.ansi() {
+                    Some(style) => out.push_str(&paint(&style, cell.ch)),
+                    None => out.push(cell.ch),
+                }
+            }
+            out.push('\n');
+        }
+        if let Some(caption) = &self.caption {
+            out.push('\n');
+            out.push_str(caption);
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Something which problems can emit frames to.
+///
+/// Frames are built by a closure which is only called if the visualizer is recording, so emitting
+/// frames to [`Disabled`] costs nothing but the call.
+pub trait Visualizer {
+    fn emit(&mut self, frame: &mut dyn FnMut() -> Frame);
+}
+
+/// A visualizer which throws every frame away without building it.
+#[allow(dead_code)]
+pub struct Disabled;
+
+impl Visualizer for Disabled {
+    fn emit(&mut self, _frame: &mut dyn FnMut() -> Frame) {}
+}
+
+/// A visualizer which keeps every frame so that it can be played back afterwards.
+#[derive(Default)]
+pub struct Recording {
+    pub frames: Vec<Frame>,
+}
+
+impl Visualizer for Recording {
+    fn emit(&mut self, frame: &mut dyn FnMut() -> Frame) {
+        self.frames.push(frame());
+    }
+}
+
+/// The pixel size of each cell when a recording is encoded as an image.
+const CELL_PIXELS: u16 = 6;
+
+impl Recording {
+    /// Prints each frame over the previous one, showing the given number of frames per second.
+    pub fn replay(&self, fps: f64) {
+        let delay = Duration::from_secs_f64(1.0 / fps.max(0.001));
+        for (i, frame) in self.frames.iter().enumerate() {
+            if i > 0 {
+                thread::sleep(delay);
+                print!("\x1b[2J\x1b[H");
+            }
+            print!("{}", frame.to_ansi());
+        }
+    }
+
+    /// Encodes the frames as an animated GIF, drawing each cell as a square of its color.
+    ///
+    /// Blank cells and `.` are left as the background so that the shape of the grid stands out.
+    pub fn write_gif(&self, writer: impl io::Write, fps: f64) -> Result<(), gif::EncodingError> {
+        let columns = self
+            .frames
+            .iter()
+            .map(|f| f.grid.width())
+            .max()
+            .unwrap_or(0);
+        let rows = self
+            .frames
+            .iter()
+            .map(|f| f.grid.height())
+            .max()
+            .unwrap_or(0);
+        let width = (columns as u16).saturating_mul(CELL_PIXELS).max(1);
+        let height = (rows as u16).saturating_mul(CELL_PIXELS).max(1);
+
+        // The background comes first, followed by each color in order.
+        let palette: Vec<u8> = std::iter::once([0x10, 0x10, 0x10])
+            .chain(Color::ALL.iter().map(Color::rgb))
+            .flatten()
+            .collect();
+        let mut encoder = gif::Encoder::new(writer, width, height, &palette)?;
+        encoder.set_repeat(gif::Repeat::Infinite)?;
+
+        let delay = (100.0 / fps.max(0.001)).round().min(u16::MAX as f64) as u16;
+        for frame in &self.frames {
+            let mut pixels = vec![0; width as usize * height as usize];
+            for point in frame.grid.points() {
+                let cell = frame.grid.get(point).expect("Points are inside the grid.");
+                if cell.ch == ' ' || cell.ch == '.' {
+                    continue;
+                }
+
+                let index = 1 + Color::ALL
+                    .iter()
+                    .position(|&c| c == cell.color)
+                    .unwrap_or(0);
+                for dy in 0..CELL_PIXELS as usize {
+                    let y = point.y * CELL_PIXELS as usize + dy;
+                    let x = point.x * CELL_PIXELS as usize;
+                    if y < height as usize && x < width as usize {
+                        let start = y * width as usize + x;
+                        let end = (start + CELL_PIXELS as usize).min((y + 1) * width as usize);
+                        pixels[start..end].fill(index as u8);
+                    }
+                }
+            }
+
+            let mut image = gif::Frame::from_indexed_pixels(width, height, pixels, None);
+            image.delay = delay;
+            encoder.write_frame(&image)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the frames to a GIF file.
+    pub fn save_gif(&self, path: &Path, fps: f64) -> io::Result<()> {
+        let file = File::create(path)?;
+        self.write_gif(file, fps).map_err(|err| match err {
+            gif::EncodingError::Io(err) => err,
+            err => io::Error::other(err),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_frames_for_the_terminal() {
+        let mut frame = Frame::from_text("ab\nc").with_caption("done");
+        frame.paint(Point::new(1, 0), Color::Red);
+        assert_eq!(
+            frame.to_ansi(),
+            format!("a{}\nc \n\ndone\n", paint(ansi::RED, 'b'))
+        );
+    }
+
+    #[test]
+    fn only_builds_frames_when_recording() {
+        let mut built = 0;
+        let mut build = || {
+            built += 1;
+            Frame::from_text("a")
+        };
+        Disabled.emit(&mut build);
+
+        let mut recording = Recording::default();
+        recording.emit(&mut build);
+        assert_eq!(built, 1);
+        assert_eq!(recording.frames.len(), 1);
+    }
+
+    #[test]
+    fn encodes_recordings_as_gifs() {
+        let mut recording = Recording::default();
+        recording.emit(&mut || Frame::from_text("#.\n.#"));
+        recording.emit(&mut || Frame::from_text("##\n##"));
+
+        let mut bytes = vec![];
+        recording.write_gif(&mut bytes, 10.0).unwrap();
+        assert!(bytes.starts_with(b"GIF89a"));
+
+        let mut decoder = gif::DecodeOptions::new()
+            .read_info(bytes.as_slice())
+            .unwrap();
+        assert_eq!((decoder.width(), decoder.height()), (12, 12));
+        let mut frames = 0;
+        while decoder.read_next_frame().unwrap().is_some() {
+            frames += 1;
+        }
+        assert_eq!(frames, 2);
+    }
+}
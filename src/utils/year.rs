@@ -1,6 +1,10 @@
-use inquire::Select;
-
-use super::problem::Problem;
+use super::{
+    cli::Flags,
+    menu::{self, Navigation},
+    parameters::Parameters,
+    problem::Problem,
+    session::Session,
+};
 
 pub struct Year {
     pub id: u32,
@@ -20,10 +24,33 @@ impl Year {
         self
     }
 
-    pub fn run(&self) {
-        let options = self.problems.iter().map(|p| p.id()).collect();
-        (Select::new("Problem:", options).prompt().ok())
-            .and_then(|id| self.problems.iter().find(|p| p.id() == id))
-            .and_then(|p| Some(p.run()));
+    /// Gets the problem registered for the given day, if any.
+    pub fn problem(&self, day: u32) -> Option<&dyn Problem> {
+        (self.problems.iter())
+            .find(|p| p.day() == day)
+            .map(|p| p.as_ref())
+    }
+
+    /// Applies the runtime parameters to every problem, stopping at the first which rejects them.
+    pub fn configure(&mut self, params: &Parameters) -> Result<(), String> {
+        for problem in &mut self.problems {
+            (problem.configure(params)).map_err(|err| format!("{}: {}", problem.id(), err))?;
+        }
+        Ok(())
+    }
+
+    /// Lets the user pick problems to run until they go back or quit.
+    pub fn run(&self, flags: &Flags) -> Navigation {
+        loop {
+            let options = self.problems.iter().map(|p| p.as_ref()).collect();
+            let nav = match menu::choose("Problem:", options) {
+                Ok(problem) => Session::new(self.id, problem, flags).run(),
+                Err(nav) => return nav,
+            };
+
+            if nav == Navigation::Quit {
+                return nav;
+            }
+        }
     }
 }